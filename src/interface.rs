@@ -1,7 +1,40 @@
 use crate::modes::*;
+use crate::settings;
 use pancurses::{initscr, endwin, noecho};
 
+use std::path::PathBuf;
+use std::{env, fs};
+
+// settings are kept in $XDG_CONFIG_HOME/virpn/settings, or
+// ~/.config/virpn/settings
+fn settings_path() -> Option<PathBuf> {
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config.join("virpn").join("settings"))
+}
+
+fn load_settings() {
+    if let Some(s) = settings_path().and_then(|path| fs::read_to_string(path).ok()) {
+        settings::update(|settings| settings.deserialize(&s));
+    }
+}
+
+// failing to save settings is not worth interrupting the user for
+fn save_settings() {
+    if let Some(path) = settings_path() {
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+
+        let _ = fs::write(path, settings::get().serialize());
+    }
+}
+
 pub fn interface() {
+    load_settings();
+
     let mut ui = Ui::build();
     let window = initscr();
     window.keypad(true);
@@ -11,9 +44,15 @@ pub fn interface() {
         ui.show(&window);
         let k = window.getch().unwrap();
 
+        let before = settings::get();
+
         ui.call_history();
 
         ui.eval_key(k);
+
+        if settings::get() != before {
+            save_settings();
+        }
     }
 
     endwin();
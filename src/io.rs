@@ -227,13 +227,38 @@ pub fn render_command(cmd: &str, cursor_loc: usize, width: usize)
     }
 }
 
+// the line between the stack and the command, showing the last error, and
+// the status of the settings on the right if there is room
+pub fn render_separator(width: usize, error: Option<&String>, status: &str) -> String {
+    let suffix = format!(" {} ==", status);
+
+    // widths are counted in chars, since errors and units can have symbols
+    // like the degree sign which take more than one byte
+    let suffix_width = suffix.chars().count();
+    let error_width = error.map_or(0, |e| e.chars().count());
+
+    match error {
+        Some(e) if error_width + suffix_width + 5 <= width => {
+            format!("== {} {}{}", e, "=".repeat(width.saturating_sub(error_width + suffix_width + 4)), suffix)
+        }
+        Some(e) if error_width + 4 <= width => {
+            format!("== {} {}", e, "=".repeat(width.saturating_sub(error_width + 4)))
+        }
+        Some(e) => e.chars().take(width).collect(),
+        None if suffix_width < width => {
+            format!("{}{}", "=".repeat(width.saturating_sub(suffix_width)), suffix)
+        }
+        None => "=".repeat(width)
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
-pub fn print_command(window: &Window, cmd: &str, cursor_loc: usize) {
+pub fn print_command(window: &Window, cmd: &str, cursor_loc: usize, error: Option<&String>, status: &str) {
     let width  = window.get_max_x() as usize;
     let height = window.get_max_y();
 
     window.mv(height - 2, 0);
-    window.addstr(&"=".repeat(width));
+    window.addstr(&render_separator(width, error, status));
 
     window.mv(height - 1, 0);
     window.clrtoeol();
@@ -245,3 +270,25 @@ pub fn print_command(window: &Window, cmd: &str, cursor_loc: usize) {
 
     window.refresh();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separators_fill_the_width_in_chars() {
+        let error = "cannot convert 1 °C to m".to_string();
+
+        for width in 0..40 {
+            for status in ["deg", "°"].iter() {
+                let line = render_separator(width, Some(&error), status);
+                assert!(line.chars().count() <= width, "{:?}", line);
+
+                let line = render_separator(width, None, status);
+                assert_eq!(line.chars().count(), width, "{:?}", line);
+            }
+        }
+
+        assert_eq!(render_separator(40, Some(&error), "°").chars().count(), 40);
+    }
+}
@@ -3,6 +3,9 @@ mod io;
 mod stack;
 mod modes;
 mod data;
+mod word;
+mod units;
+mod settings;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod interface;
@@ -108,7 +108,7 @@ impl Mode for Line_edit_mode {
                 self.strs_hist.clear();
                 self.loc = self.strs.len();
 
-                if let Num(_) = i {
+                if let Num(_) | Rational(_) = i {
                     self.tokenize_num = true;
                 }
 
@@ -3,13 +3,18 @@ pub use std::sync::Arc;
 pub use std::cell::Cell;
 
 pub use crate::stack::Stack;
-pub use crate::stack::{Item, Item::*};
+pub use crate::stack::{Item, Item::*, complex_item, quantity_item, scale_item};
 pub use crate::data::*;
+pub use crate::word::Word;
+pub use crate::units::{Units, UnitRule, UnitRule::*};
 pub use crate::io::*;
+use crate::settings;
 
 use self::{
     number::Number_mode,
+    unit::Unit_mode,
     ops::Op_mode,
+    tvm::Tvm_mode,
     var::Var_mode,
     history::History_mode,
     line_edit::Line_edit_mode
@@ -20,7 +25,9 @@ pub use regex::Regex;
 pub use std::mem;
 
 pub mod number;
+pub mod unit;
 pub mod ops;
+pub mod tvm;
 pub mod var;
 pub mod history;
 pub mod line_edit;
@@ -114,6 +121,8 @@ impl Ui {
     pub fn build() -> Self {
         Self::build_from_modes(vec![
             Box::new(Number_mode{}),
+            Box::new(Unit_mode{}),
+            Box::new(Tvm_mode::new()),
             Box::new(Op_mode::new()),
             Box::new(Var_mode::new()),
             Box::new(History_mode::new()),
@@ -217,6 +226,7 @@ impl Ui {
                     self.run_mode(Vec::new());
                 }
             } else {
+                self.stack.clear_error();
                 self.eval(s);
                 self.call_history();
             }
@@ -372,6 +382,6 @@ impl Ui {
     #[cfg(not(target_arch = "wasm32"))]
     pub fn show(&self, window: &pancurses::Window) {
         print_stack(&window, &self.stack);
-        print_command(&window, &self.print, self.cursor);
+        print_command(&window, &self.print, self.cursor, self.stack.get_error(), &settings::get().status());
     }
 }
@@ -1,10 +1,45 @@
 use crate::modes::*;
+use num_rational::BigRational;
+use num_complex::Complex64;
+use crate::word::parse_word;
 
 #[allow(non_camel_case_types)]
 #[derive(Clone, Debug)]
 pub struct Number_mode {}
 
 const NUM_CHRS: &str = ";asdfghjkl";
+const HEX_CHRS: &str = "ABCDEF";
+
+// parses complex literals of the form "3-4i" or "2.5i"
+fn parse_complex(s: &str) -> Option<Complex64> {
+    let s = s.strip_suffix('i')?;
+    let split = s.char_indices()
+        .skip(1)
+        .filter(|(i, c)| (*c == '+' || *c == '-') && &s[i - 1..*i] != "e")
+        .map(|(i, _)| i)
+        .last();
+
+    match split {
+        Some(i) => Some(Complex64::new(s[..i].parse().ok()?, s[i..].parse().ok()?)),
+        None => Some(Complex64::new(0., s.parse().ok()?))
+    }
+}
+
+// reads hours, minutes and seconds, like 1:30:15, as a number of hours
+fn parse_duration(s: &str) -> Option<f64> {
+    let (sign, s) = match s.strip_prefix('-') {
+        Some(s) => (-1., s),
+        None => (1., s)
+    };
+
+    let mut out = 0.;
+
+    for (i, part) in s.split(':').enumerate() {
+        out += part.parse::<f64>().ok()? / 60f64.powi(i as i32);
+    }
+
+    Some(sign * out)
+}
 
 impl Mode for Number_mode {
     fn get_bindings(&self) -> Vec<Vec<Input>> {
@@ -15,12 +50,21 @@ impl Mode for Number_mode {
 
         out.push(vec![Character('n')]);
         out.push(vec![Character('m')]);
+        out.push(vec![Character('x')]);
+        out.push(vec![Character(':')]);
 
         out
     }
 
     fn get_operator_regex(&self) -> Regex {
-        Regex::new(r"^-?\d*\.?\d+e?\d*").unwrap()
+        Regex::new(concat!(
+            r"^-?\d+:\d*\.?\d+(:\d*\.?\d+)?|",
+            r"^-?(0x[0-9a-fA-F]+|0o[0-7]+|0b[01]+)([ui](8|16|32|64))?|",
+            r"^-?\d+[ui](8|16|32|64)|",
+            r"^-?\d+/\d+|",
+            r"^-?\d*\.?\d+(e-?\d+)?([+-]\d*\.?\d+(e-?\d+)?)?i|",
+            r"^-?\d*\.?\d+e?\d*"
+        )).unwrap()
     }
 
     fn get_name(&self) -> String {
@@ -28,7 +72,25 @@ impl Mode for Number_mode {
     }
 
     fn eval_operators(&mut self, ui: &mut Ui, op: &str) {
-        if let Ok(f) = op.parse::<f64>() {
+        if op.contains(':') {
+            if let Some(x) = parse_duration(op) {
+                ui.get_stack().push(Num(x));
+            }
+        } else if op.ends_with('i') {
+            if let Some(z) = parse_complex(op) {
+                ui.get_stack().push(complex_item(z));
+            }
+        } else if op.contains(['x', 'o', 'b', 'u', 'i']) {
+            if let Some(w) = parse_word(op) {
+                ui.get_stack().push(Integer(w));
+            }
+        } else if !op.contains('.') && !op.contains('e') {
+            // integers and fractions are exact, anything with a decimal point
+            // or exponent is a float
+            if let Ok(r) = op.parse::<BigRational>() {
+                ui.get_stack().push(Rational(r));
+            }
+        } else if let Ok(f) = op.parse::<f64>() {
              ui.get_stack().push(Num(f));
         }
 
@@ -56,6 +118,14 @@ impl Mode for Number_mode {
         msg.push(EscBind(vec![KeyRight]));
         msg.push(EscBind(bind_from_str(" ")));
 
+        let hex = buffer.contains('x');
+
+        if hex {
+            for c in HEX_CHRS.chars() {
+                msg.push(EscBind(vec![Character(c)]));
+            }
+        }
+
         match bind[0] {
             Character('n') => {
                 if loc == 0 && !buffer.contains('-') {
@@ -64,11 +134,30 @@ impl Mode for Number_mode {
                 }
             }
             Character('m') => {
-                if !buffer.contains('.') {
+                if !buffer.contains('.') && !hex && !buffer[loc..].contains(':') {
                     buffer.insert(loc, '.');
                     loc += 1;
                 }
             }
+            // separates hours, minutes and seconds, like 1:30:15
+            Character(':') => {
+                let parts = buffer.matches(':').count();
+
+                if parts < 2 && !hex && !buffer[..loc].contains('.') && loc > 0 {
+                    buffer.insert(loc, ':');
+                    loc += 1;
+                }
+            }
+            Character('x') => {
+                if !hex && !buffer.contains('.') && buffer.trim_start_matches('-').is_empty() {
+                    buffer.push_str("0x");
+                    loc = buffer.len();
+
+                    for c in HEX_CHRS.chars() {
+                        msg.push(EscBind(vec![Character(c)]));
+                    }
+                }
+            }
 
             Character(' ') => {
                 ret = true;
@@ -85,6 +174,9 @@ impl Mode for Number_mode {
                 if let Some(i) = NUM_CHRS.find(c) {
                     buffer.insert_str(loc, &i.to_string());
                     loc += 1;
+                } else if hex && HEX_CHRS.contains(c) {
+                    buffer.insert(loc, c);
+                    loc += 1;
                 }
             }
             _ => {}
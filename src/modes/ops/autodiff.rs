@@ -0,0 +1,846 @@
+use crate::modes::*;
+use crate::modes::ops::func_helpers::{call_func, call_func_nums};
+use crate::settings::angle_scale;
+
+use std::f64::consts;
+
+// a truncated taylor series, where the kth term is the kth derivative over k!
+#[derive(Clone, Debug)]
+pub struct Jet(Vec<f64>);
+
+impl Jet {
+    fn constant(x: f64, order: usize) -> Jet {
+        let mut out = vec![0.; order + 1];
+        out[0] = x;
+        Jet(out)
+    }
+
+    fn variable(x: f64, order: usize) -> Jet {
+        let mut out = Jet::constant(x, order);
+
+        if order > 0 {
+            out.0[1] = 1.;
+        }
+        out
+    }
+
+    fn value(&self) -> f64 {
+        self.0[0]
+    }
+
+    fn is_constant(&self) -> bool {
+        self.0[1..].iter().all(|x| *x == 0.)
+    }
+
+    fn map(&self, f: impl Fn(f64) -> f64) -> Jet {
+        Jet(self.0.iter().map(|x| f(*x)).collect())
+    }
+
+    fn zip(&self, other: &Jet, f: impl Fn(f64, f64) -> f64) -> Jet {
+        Jet(self.0.iter().zip(&other.0).map(|(x, y)| f(*x, *y)).collect())
+    }
+
+    fn add(&self, other: &Jet) -> Jet {
+        self.zip(other, |x, y| x + y)
+    }
+
+    fn sub(&self, other: &Jet) -> Jet {
+        self.zip(other, |x, y| x - y)
+    }
+
+    fn scale(&self, factor: f64) -> Jet {
+        self.map(|x| x * factor)
+    }
+
+    fn mul(&self, other: &Jet) -> Jet {
+        let (a, b) = (&self.0, &other.0);
+
+        Jet((0..a.len())
+            .map(|k| (0..=k).map(|j| a[j] * b[k - j]).sum())
+            .collect())
+    }
+
+    fn div(&self, other: &Jet) -> Jet {
+        let (a, b) = (&self.0, &other.0);
+        let mut q: Vec<f64> = Vec::new();
+
+        for k in 0..a.len() {
+            let sum: f64 = (1..=k).map(|j| b[j] * q[k - j]).sum();
+            q.push((a[k] - sum) / b[0]);
+        }
+        Jet(q)
+    }
+
+    fn recip(&self) -> Jet {
+        Jet::constant(1., self.0.len() - 1).div(self)
+    }
+
+    // the series w with w(0) = value and w' = g u', where u is self
+    fn compose(&self, value: f64, g: &Jet) -> Jet {
+        let u = &self.0;
+        let mut w = vec![value];
+
+        for k in 1..u.len() {
+            let sum: f64 = (1..=k).map(|j| j as f64 * u[j] * g.0[k - j]).sum();
+            w.push(sum / k as f64);
+        }
+        Jet(w)
+    }
+
+    fn exp(&self) -> Jet {
+        let u = &self.0;
+        let mut w = vec![u[0].exp()];
+
+        for k in 1..u.len() {
+            let sum: f64 = (1..=k).map(|j| j as f64 * u[j] * w[k - j]).sum();
+            w.push(sum / k as f64);
+        }
+        Jet(w)
+    }
+
+    fn ln(&self) -> Jet {
+        self.compose(self.value().ln(), &self.recip())
+    }
+
+    fn sin_cos(&self) -> (Jet, Jet) {
+        let u = &self.0;
+        let mut s = vec![u[0].sin()];
+        let mut c = vec![u[0].cos()];
+
+        for k in 1..u.len() {
+            let ds: f64 = (1..=k).map(|j| j as f64 * u[j] * c[k - j]).sum();
+            let dc: f64 = (1..=k).map(|j| j as f64 * u[j] * s[k - j]).sum();
+
+            s.push(ds / k as f64);
+            c.push(-dc / k as f64);
+        }
+        (Jet(s), Jet(c))
+    }
+
+    // self^p for a constant p, with the value of the result given. the
+    // recurrence divides by the value of self, so there is no series at zero,
+    // even where the derivative exists
+    fn pow_const(&self, p: f64, value: f64) -> Option<Jet> {
+        let u = &self.0;
+        let mut w = vec![value];
+
+        if u[0] == 0. && u.len() > 1 {
+            return None;
+        }
+
+        for k in 1..u.len() {
+            let sum: f64 = (1..=k)
+                .map(|j| ((p + 1.) * j as f64 - k as f64) * u[j] * w[k - j])
+                .sum();
+
+            w.push(sum / (k as f64 * u[0]));
+        }
+        Some(Jet(w))
+    }
+
+    fn powi(&self, n: i64) -> Jet {
+        let mut out = Jet::constant(1., self.0.len() - 1);
+        let mut base = self.clone();
+        let mut e = n.unsigned_abs();
+
+        while e > 0 {
+            if e & 1 == 1 {
+                out = out.mul(&base);
+            }
+            base = base.mul(&base);
+            e >>= 1;
+        }
+
+        if n < 0 {out.recip()} else {out}
+    }
+
+    fn pow(&self, y: &Jet) -> Option<Jet> {
+        let p = y.value();
+
+        if !y.is_constant() {
+            Some(y.mul(&self.ln()).exp())
+        } else if p.fract() == 0. && p.abs() <= 64. {
+            Some(self.powi(p as i64))
+        } else {
+            self.pow_const(p, self.value().powf(p))
+        }
+    }
+
+    fn sqrt(&self) -> Option<Jet> {
+        self.pow_const(0.5, self.value().sqrt())
+    }
+
+    fn cbrt(&self) -> Option<Jet> {
+        self.pow_const(1. / 3., self.value().cbrt())
+    }
+
+    // 1 + sign * self^2, used by the inverse trig functions
+    fn one_plus_square(&self, sign: f64) -> Jet {
+        let one = Jet::constant(1., self.0.len() - 1);
+        one.add(&self.mul(self).scale(sign))
+    }
+
+    fn unary(&self, op: &str) -> Option<Jet> {
+        let out = match op {
+            "negate" => self.scale(-1.),
+            "invert" => self.recip(),
+            "square" => self.mul(self),
+            "sqrt" => self.sqrt()?,
+            "cbrt" => self.cbrt()?,
+            // abs has a kink at zero, where it has no derivative
+            "abs" if self.value() == 0. => return None,
+            "abs" => self.scale(self.value().signum()),
+            "pow" => self.exp(),
+            "pow2" => self.scale(consts::LN_2).exp(),
+            "pow10" => self.scale(consts::LN_10).exp(),
+            "ln" => self.ln(),
+            "log10" => self.ln().scale(1. / consts::LN_10),
+            "log2" => self.ln().scale(1. / consts::LN_2),
+            "deg" => self.scale(180. / consts::PI),
+            "rad" => self.scale(consts::PI / 180.),
+            // trigonometric functions work in the current angle mode
+            "sin" => self.scale(angle_scale()).sin_cos().0,
+            "cos" => self.scale(angle_scale()).sin_cos().1,
+            "tan" => {
+                let (s, c) = self.scale(angle_scale()).sin_cos();
+                s.div(&c)
+            }
+            "asin" => self.compose(self.value().asin(), &self.one_plus_square(-1.).sqrt()?.recip())
+                .scale(1. / angle_scale()),
+            "acos" => self.compose(self.value().acos(), &self.one_plus_square(-1.).sqrt()?.recip().scale(-1.))
+                .scale(1. / angle_scale()),
+            "atan" => self.compose(self.value().atan(), &self.one_plus_square(1.).recip())
+                .scale(1. / angle_scale()),
+            "sinh" => self.exp().sub(&self.scale(-1.).exp()).scale(0.5),
+            "cosh" => self.exp().add(&self.scale(-1.).exp()).scale(0.5),
+            "tanh" => {
+                let (a, b) = (self.exp(), self.scale(-1.).exp());
+                a.sub(&b).div(&a.add(&b))
+            }
+            "asinh" => self.compose(self.value().asinh(), &self.one_plus_square(1.).sqrt()?.recip()),
+            "acosh" => self.compose(self.value().acosh(), &self.one_plus_square(-1.).scale(-1.).sqrt()?.recip()),
+            "atanh" => self.compose(self.value().atanh(), &self.one_plus_square(-1.).recip()),
+            "re" | "conj" | "approx" => self.clone(),
+            _ => return None
+        };
+        Some(out)
+    }
+
+    fn binary(&self, other: &Jet, op: &str) -> Option<Jet> {
+        let out = match op {
+            "+" => self.add(other),
+            "-" => self.sub(other),
+            "*" => self.mul(other),
+            "/" => self.div(other),
+            "^" => self.pow(other)?,
+            "nth_rt" => self.pow(&other.recip())?,
+            "log" => self.ln().div(&other.ln()),
+            _ => return None
+        };
+        Some(out)
+    }
+
+    // the stack gives complex results outside the domain of real functions,
+    // which the series has no way to follow
+    fn real_from(self, args: &[&Jet]) -> Option<Jet> {
+        if self.value().is_nan() && args.iter().all(|x| !x.value().is_nan()) {
+            None
+        } else {
+            Some(self)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Value {
+    One(Jet),
+    Many(Vec<Value>),
+}
+
+use Value::*;
+
+impl Value {
+    fn from_item(item: &Item, order: usize) -> Option<Value> {
+        match item {
+            List(l) => l.iter().map(|i| Value::from_item(i, order)).collect::<Option<_>>().map(Many),
+            _ => item.as_f64().map(|x| One(Jet::constant(x, order)))
+        }
+    }
+
+    fn map(&self, f: &impl Fn(&Jet) -> Option<Jet>) -> Option<Value> {
+        match self {
+            One(x) => f(x).map(One),
+            Many(l) => l.iter().map(|x| x.map(f)).collect::<Option<_>>().map(Many)
+        }
+    }
+
+    // pairs up lists element by element, like operators on the stack do
+    fn map_2(&self, other: &Value, f: &impl Fn(&Jet, &Jet) -> Option<Jet>) -> Option<Value> {
+        match (self, other) {
+            (One(x), One(y)) => f(x, y).map(One),
+            (Many(l), y @ One(_)) => l.iter().map(|x| x.map_2(y, f)).collect::<Option<_>>().map(Many),
+            (x @ One(_), Many(l)) => l.iter().map(|y| x.map_2(y, f)).collect::<Option<_>>().map(Many),
+            (Many(l), Many(m)) => l.iter().zip(m).map(|(x, y)| x.map_2(y, f)).collect::<Option<_>>().map(Many),
+        }
+    }
+
+    fn fold(&self, state: Jet, f: &impl Fn(&Jet, &Jet) -> Jet) -> Jet {
+        match self {
+            One(x) => f(&state, x),
+            Many(l) => l.iter().fold(state, |state, x| x.fold(state, f)),
+        }
+    }
+}
+
+// the operators the machine runs, by their names in the op table. a func
+// using any other operator falls back to numeric derivatives. the stack ops
+// are matched by name in run_op, so their list is only needed by the tests
+#[cfg(test)]
+const STACK_OPS: [&str; 17] = [
+    "swap", "rotate", "dup", "pop", "rev", "clear", "down", "new_list", "up",
+    "sum", "msum", "sum_list", "msum_list", "pi", "e", "sqrt_2", "epsilon",
+];
+
+const UNARY_OPS: [&str; 29] = [
+    "negate", "invert", "square", "sqrt", "cbrt", "abs", "pow", "pow2",
+    "pow10", "ln", "log10", "log2", "deg", "rad", "sin", "cos", "tan", "asin",
+    "acos", "atan", "sinh", "cosh", "tanh", "asinh", "acosh", "atanh", "re",
+    "conj", "approx",
+];
+
+const BINARY_OPS: [&str; 7] = ["+", "-", "*", "/", "^", "nth_rt", "log"];
+
+// runs a func on series instead of numbers, mirroring the behaviour of the
+// stack for the operators which are differentiable
+struct Machine {
+    above: Vec<Vec<Value>>,
+    curr: Vec<Value>,
+    order: usize,
+}
+
+impl Machine {
+    fn pop(&mut self) -> Option<Value> {
+        self.curr.pop()
+    }
+
+    fn fold_all(&mut self, start: f64, f: impl Fn(&Jet, &Jet) -> Jet) {
+        let state = Jet::constant(start, self.order);
+        let out = self.curr.iter().fold(state, |state, x| x.fold(state, &f));
+
+        self.curr.clear();
+        self.curr.push(One(out));
+    }
+
+    fn fold_list(&mut self, start: f64, f: impl Fn(&Jet, &Jet) -> Jet) -> Option<()> {
+        match self.pop()? {
+            Many(l) => {
+                let state = Jet::constant(start, self.order);
+                let out = l.iter().fold(state, |state, x| x.fold(state, &f));
+
+                self.curr.push(One(out));
+            }
+            x => self.curr.push(x)
+        }
+        Some(())
+    }
+
+    fn constant(&mut self, x: f64) {
+        self.curr.push(One(Jet::constant(x, self.order)));
+    }
+
+    fn run_op(&mut self, op: &str) -> Option<()> {
+        match op {
+            "swap" => {
+                if self.curr.len() >= 2 {
+                    let y = self.pop()?;
+                    let x = self.pop()?;
+                    self.curr.push(y);
+                    self.curr.push(x);
+                }
+            }
+            "rotate" => {
+                if let Some(x) = self.pop() {
+                    self.curr.insert(0, x);
+                }
+            }
+            "dup" => {
+                if let Some(x) = self.curr.last().cloned() {
+                    self.curr.push(x);
+                }
+            }
+            "pop" => {self.pop();}
+            "rev" => self.curr.reverse(),
+            "clear" => self.curr.clear(),
+            "down" | "new_list" => {
+                let l = match self.curr.last() {
+                    Some(Many(_)) if op == "down" => {
+                        if let Some(Many(l)) = self.pop() {l} else {unreachable!()}
+                    }
+                    _ => Vec::new()
+                };
+
+                self.above.push(mem::replace(&mut self.curr, l));
+            }
+            "up" => {
+                let l = mem::replace(&mut self.curr, self.above.pop().unwrap_or_default());
+                self.curr.push(Many(l));
+            }
+            "sum" => self.fold_all(0., Jet::add),
+            "msum" => self.fold_all(1., Jet::mul),
+            "sum_list" => self.fold_list(0., Jet::add)?,
+            "msum_list" => self.fold_list(1., Jet::mul)?,
+            "pi" => self.constant(consts::PI),
+            "e" => self.constant(consts::E),
+            "sqrt_2" => self.constant(consts::SQRT_2),
+            "epsilon" => self.constant(f64::EPSILON),
+            _ if UNARY_OPS.contains(&op) => {
+                if let Some(x) = self.pop() {
+                    self.curr.push(x.map(&|x| x.unary(op)?.real_from(&[x]))?);
+                }
+            }
+            _ if BINARY_OPS.contains(&op) => {
+                if self.curr.len() >= 2 {
+                    let y = self.pop()?;
+                    let x = self.pop()?;
+                    self.curr.push(x.map_2(&y, &|x, y| x.binary(y, op)?.real_from(&[x, y]))?);
+                }
+            }
+            _ => return None
+        }
+        Some(())
+    }
+}
+
+// evaluates a func on series arguments, or returns none if it uses an
+// operator which can't be differentiated this way
+pub fn eval_series(ui: &mut Ui, f: &str, args: Vec<Value>, order: usize) -> Option<Value> {
+    let mut machine = Machine {above: Vec::new(), curr: args, order};
+
+    for (mode, op) in ui.tokenize(f) {
+        match &mode[..] {
+            "ops" => machine.run_op(&op)?,
+            "number" | "line edit" if !op.starts_with('(') => {
+                let item = call_func(ui, &op, Vec::new())?;
+                machine.curr.push(Value::from_item(&item, order)?);
+            }
+            _ => return None
+        }
+    }
+
+    machine.pop()
+}
+
+fn series_nums(v: &Value, k: usize) -> Option<Vec<f64>> {
+    match v {
+        One(x) => Some(vec![x.0[k]]),
+        Many(l) => l.iter()
+            .map(|x| if let One(x) = x {Some(x.0[k])} else {None})
+            .collect()
+    }
+}
+
+// ridders' extrapolation to h = 0 of difference quotients whose error is a
+// series in h^power
+fn extrapolate<F>(h: f64, power: i32, mut d: F) -> Result<Vec<f64>, String>
+    where F: FnMut(f64) -> Result<Vec<f64>, String>
+{
+    const SHRINK: f64 = 1.4;
+    const LEVELS: usize = 10;
+
+    let diff = |x: &[f64], y: &[f64]| {
+        x.iter().zip(y).map(|(x, y)| (x - y).abs()).fold(0., f64::max)
+    };
+
+    let mut h = h;
+    let mut prev: Vec<Vec<f64>> = vec![d(h)?];
+    let mut best = prev[0].clone();
+    let mut best_err = f64::INFINITY;
+
+    for i in 1..LEVELS {
+        h /= SHRINK;
+
+        let mut row = vec![d(h)?];
+        let mut factor = SHRINK.powi(power);
+
+        for j in 1..=i {
+            let next: Vec<f64> = row[j - 1].iter()
+                .zip(&prev[j - 1])
+                .map(|(x, y)| (x * factor - y) / (factor - 1.))
+                .collect();
+
+            let err = diff(&next, &row[j - 1]).max(diff(&next, &prev[j - 1]));
+
+            if err <= best_err {
+                best_err = err;
+                best = next.clone();
+            }
+
+            factor *= SHRINK.powi(power);
+            row.push(next);
+        }
+
+        if diff(&row[i], &prev[i - 1]) >= 2. * best_err {
+            break;
+        }
+        prev = row;
+    }
+
+    Ok(best)
+}
+
+// the starting step for difference quotients and guesses near x
+pub fn step_size(x: f64) -> f64 {
+    0.1 * x.abs().max(1.)
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1., |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+fn eval_num(ui: &mut Ui, f: &str, args: Vec<Item>) -> Result<f64, String> {
+    match call_func_nums(ui, f, args)?[..] {
+        [y] => Ok(y),
+        _ => Err("expected the func to return a number".to_string())
+    }
+}
+
+pub fn nth_derivative(ui: &mut Ui, f: &str, x: f64, n: usize) -> Result<f64, String> {
+    if let Some(One(y)) = eval_series(ui, f, vec![One(Jet::variable(x, n))], n) {
+        let factorial: f64 = (1..=n).map(|k| k as f64).product();
+        return Ok(y.0[n] * factorial);
+    }
+
+    if n == 0 {
+        return eval_num(ui, f, vec![Num(x)]);
+    }
+
+    // central differences, which have an error series in h^2, or forward
+    // differences, with an error series in h, where the func is only defined
+    // on one side of x, like powers at zero
+    let mut differences = |offset: f64, power: i32| extrapolate(step_size(x), power, |h| {
+        let mut sum = 0.;
+
+        for k in 0..=n {
+            let y = eval_num(ui, f, vec![Num(x + (offset - k as f64) * h)])?;
+            let sign = if k % 2 == 0 {1.} else {-1.};
+
+            sum += sign * binomial(n, k) * y;
+        }
+
+        Ok(vec![sum / h.powi(n as i32)])
+    });
+
+    let out = differences(n as f64 / 2., 2).or_else(|_| differences(n as f64, 1))?;
+
+    Ok(out[0])
+}
+
+// the partial derivatives of a func taking each coordinate as an argument
+pub fn gradient(ui: &mut Ui, f: &str, xs: &[f64]) -> Result<Vec<f64>, String> {
+    let seeded = |i: usize| -> Vec<Value> {
+        xs.iter()
+            .enumerate()
+            .map(|(j, x)| One(if i == j {Jet::variable(*x, 1)} else {Jet::constant(*x, 1)}))
+            .collect()
+    };
+
+    let exact: Option<Vec<f64>> = (0..xs.len())
+        .map(|i| match eval_series(ui, f, seeded(i), 1) {
+            Some(One(y)) => Some(y.0[1]),
+            _ => None
+        })
+        .collect();
+
+    if let Some(out) = exact {
+        return Ok(out);
+    }
+
+    (0..xs.len())
+        .map(|i| {
+            let at = |ui: &mut Ui, h: f64| {
+                let mut args: Vec<Item> = xs.iter().map(|x| Num(*x)).collect();
+                args[i] = Num(xs[i] + h);
+                eval_num(ui, f, args)
+            };
+
+            extrapolate(step_size(xs[i]), 2, |h| {
+                Ok(vec![(at(ui, h)? - at(ui, -h)?) / (2. * h)])
+            }).map(|d| d[0])
+        })
+        .collect()
+}
+
+// the matrix of partial derivatives of a func from a list to a list, with a
+// row for each output
+pub fn jacobian(ui: &mut Ui, f: &str, xs: &[f64]) -> Result<Vec<Vec<f64>>, String> {
+    let seeded = |i: usize| -> Vec<Value> {
+        vec![Many(xs.iter()
+            .enumerate()
+            .map(|(j, x)| One(if i == j {Jet::variable(*x, 1)} else {Jet::constant(*x, 1)}))
+            .collect())]
+    };
+
+    let exact: Option<Vec<Vec<f64>>> = (0..xs.len())
+        .map(|i| eval_series(ui, f, seeded(i), 1).and_then(|y| series_nums(&y, 1)))
+        .collect();
+
+    let columns = match exact {
+        Some(columns) => columns,
+        None => (0..xs.len())
+            .map(|i| {
+                let at = |ui: &mut Ui, h: f64| {
+                    let mut args: Vec<Item> = xs.iter().map(|x| Num(*x)).collect();
+                    args[i] = Num(xs[i] + h);
+                    call_func_nums(ui, f, vec![List(args)])
+                };
+
+                extrapolate(step_size(xs[i]), 2, |h| {
+                    let (a, b) = (at(ui, h)?, at(ui, -h)?);
+                    Ok(a.iter().zip(b).map(|(a, b)| (a - b) / (2. * h)).collect())
+                })
+            })
+            .collect::<Result<_, _>>()?
+    };
+
+    let rows = columns.first().map_or(0, Vec::len);
+
+    if columns.iter().any(|c| c.len() != rows) {
+        return Err("expected the func to return lists of the same length".to_string());
+    }
+
+    Ok((0..rows).map(|r| columns.iter().map(|c| c[r]).collect()).collect())
+}
+
+fn point(item: &Item) -> Result<Vec<f64>, String> {
+    match item {
+        List(l) => l.iter().map(Item::as_f64).collect(),
+        _ => None
+    }.ok_or_else(|| "expected a list of numbers for the point".to_string())
+}
+
+pub fn slope_item(ui: &mut Ui, args: &[Item]) -> Result<Vec<Item>, String> {
+    match args {
+        [Func(f), x] => {
+            let x = x.as_f64().ok_or_else(|| "expected a number".to_string())?;
+            Ok(vec![Num(nth_derivative(ui, f, x, 1)?)])
+        }
+        _ => Err("expected a func and a number".to_string())
+    }
+}
+
+pub fn nth_derivative_item(ui: &mut Ui, args: &[Item]) -> Result<Vec<Item>, String> {
+    match args {
+        [Func(f), x, n] => {
+            let x = x.as_f64().ok_or_else(|| "expected a number".to_string())?;
+            let n = n.as_f64()
+                .filter(|n| n.fract() == 0. && (0. ..=20.).contains(n))
+                .ok_or_else(|| "expected an order from 0 to 20".to_string())?;
+
+            Ok(vec![Num(nth_derivative(ui, f, x, n as usize)?)])
+        }
+        _ => Err("expected a func, a number and an order".to_string())
+    }
+}
+
+pub fn gradient_item(ui: &mut Ui, args: &[Item]) -> Result<Vec<Item>, String> {
+    match args {
+        [Func(f), x] => {
+            let out = gradient(ui, f, &point(x)?)?;
+            Ok(vec![List(out.into_iter().map(Num).collect())])
+        }
+        _ => Err("expected a func and a point".to_string())
+    }
+}
+
+pub fn jacobian_item(ui: &mut Ui, args: &[Item]) -> Result<Vec<Item>, String> {
+    match args {
+        [Func(f), x] => {
+            let out = jacobian(ui, f, &point(x)?)?;
+
+            Ok(vec![List(out.into_iter()
+                .map(|row| List(row.into_iter().map(Num).collect()))
+                .collect())])
+        }
+        _ => Err("expected a func and a point".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(x: f64, y: f64, tol: f64) -> bool {
+        (x - y).abs() <= tol * y.abs().max(1.)
+    }
+
+    // the numbers left on the stack, or none if any of them are not real
+    fn run(line: &str) -> Option<Vec<f64>> {
+        let mut ui = Ui::build();
+        ui.eval(line.to_string());
+
+        let stack = ui.get_stack();
+        assert_eq!(stack.get_error(), None, "{}", line);
+
+        match stack.last() {
+            Some(List(l)) => l.iter().map(Item::as_f64).collect(),
+            Some(x) => x.as_f64().map(|x| vec![x]),
+            None => Some(Vec::new())
+        }
+    }
+
+    // checks the series of a func against running it on the stack, both for
+    // its value and for its derivative in the first argument
+    fn check(f: &str, args: &[f64]) {
+        let run_at = |h: f64| {
+            let line = args.iter().enumerate()
+                .map(|(i, x)| (if i == 0 {x + h} else {*x}).to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            run(&format!("{} {}", line, f))
+        };
+
+        let seeded = args.iter().enumerate()
+            .map(|(i, x)| One(if i == 0 {Jet::variable(*x, 1)} else {Jet::constant(*x, 1)}))
+            .collect();
+
+        let series = eval_series(&mut Ui::build(), f, seeded, 1);
+
+        let expected = match run_at(0.) {
+            Some(expected) => expected,
+            None => {
+                assert!(series.is_none(), "{} at {:?} should have no series", f, args);
+                return;
+            }
+        };
+
+        let series = series.unwrap_or_else(|| panic!("{} at {:?} has no series", f, args));
+        let value = series_nums(&series, 0).unwrap();
+        let slope = series_nums(&series, 1).unwrap();
+
+        let h = 0.0001;
+        let (above, below) = (run_at(h).unwrap(), run_at(-h).unwrap());
+
+        assert_eq!(value.len(), expected.len(), "{}", f);
+
+        for i in 0..value.len() {
+            let numeric = (above[i] - below[i]) / (2. * h);
+
+            assert!(close(value[i], expected[i], 1e-12), "{}: {} != {}", f, value[i], expected[i]);
+            assert!(close(slope[i], numeric, 1e-6), "{}: slope {} != {}", f, slope[i], numeric);
+        }
+    }
+
+    #[test]
+    fn jet_arithmetic() {
+        let x = Jet::variable(2., 3);
+        let c = Jet::constant(3., 3);
+
+        assert_eq!(x.add(&c).0, vec![5., 1., 0., 0.]);
+        assert_eq!(x.sub(&c).0, vec![-1., 1., 0., 0.]);
+        assert_eq!(x.mul(&x).0, vec![4., 4., 1., 0.]);
+        assert_eq!(x.powi(3).0, vec![8., 12., 6., 1.]);
+
+        // 1 / x = 1/2 - (x - 2)/4 + (x - 2)^2/8 - (x - 2)^3/16
+        assert_eq!(x.recip().0, vec![0.5, -0.25, 0.125, -0.0625]);
+        assert_eq!(x.mul(&x).div(&x).0, x.0);
+
+        let e = Jet::variable(0., 3).exp();
+        assert_eq!(e.0, vec![1., 1., 0.5, 1. / 6.]);
+
+        let (s, c) = Jet::variable(0., 3).sin_cos();
+        assert_eq!(s.0, vec![0., 1., 0., -1. / 6.]);
+        assert_eq!(c.0, vec![1., 0., -0.5, 0.]);
+
+        let l = Jet::variable(1., 3).ln();
+        assert_eq!(l.0, vec![0., 1., -0.5, 1. / 3.]);
+    }
+
+    #[test]
+    fn machine_ops_are_in_the_op_table() {
+        let names: Vec<String> = super::super::table::gen_ops().into_iter().map(|op| op.0).collect();
+        let x = Jet::variable(0.3, 1);
+
+        for op in STACK_OPS.iter().chain(UNARY_OPS.iter()).chain(BINARY_OPS.iter()) {
+            assert!(names.iter().any(|name| name == op), "{} is not in the op table", op);
+        }
+
+        for op in UNARY_OPS.iter() {
+            assert!(x.unary(op).is_some(), "{} has no series", op);
+        }
+
+        for op in BINARY_OPS.iter() {
+            assert!(x.binary(&x, op).is_some(), "{} has no series", op);
+        }
+
+        for op in STACK_OPS.iter() {
+            let mut machine = Machine {above: Vec::new(), curr: vec![One(x.clone()); 2], order: 1};
+            assert!(machine.run_op(op).is_some(), "{} is not run by the machine", op);
+        }
+
+        let mut machine = Machine {above: Vec::new(), curr: vec![One(x.clone()); 2], order: 1};
+        assert!(machine.run_op("floor").is_none());
+    }
+
+    #[test]
+    fn unary_ops_match_the_stack() {
+        for op in UNARY_OPS.iter().filter(|op| **op != "acosh") {
+            check(op, &[0.3]);
+            check(op, &[-0.4]);
+        }
+
+        check("acosh", &[1.5]);
+        check("acosh", &[0.5]);
+        check("abs", &[-2.]);
+    }
+
+    #[test]
+    fn binary_ops_match_the_stack() {
+        for op in BINARY_OPS.iter() {
+            check(op, &[2.5, 1.5]);
+            check(op, &[1.5, 2.5]);
+        }
+
+        check("^", &[-2., 3.]);
+    }
+
+    #[test]
+    fn stack_ops_match_the_stack() {
+        let funcs = [
+            "swap -", "rotate - -", "dup *", "pop", "rev - -", "clear pi",
+            "new_list swap up", "sum", "msum", "up sum_list", "up msum_list",
+            "down 2 * up", "e sqrt_2 epsilon + *", "2 * 1 +", "[1 2] *",
+        ];
+
+        for f in funcs.iter() {
+            check(f, &[1.5, 2.5, 3.5]);
+        }
+    }
+
+    #[test]
+    fn singular_points_have_no_series() {
+        for f in ["abs", "sqrt", "2.5 ^"].iter() {
+            let x = vec![One(Jet::variable(0., 1))];
+            assert!(eval_series(&mut Ui::build(), f, x, 1).is_none(), "{}", f);
+        }
+    }
+
+    #[test]
+    fn powers_at_zero_fall_back_to_numeric_slopes() {
+        let mut ui = Ui::build();
+
+        assert!(close(nth_derivative(&mut ui, "2.5 ^", 0., 1).unwrap(), 0., 1e-4));
+        assert!(close(nth_derivative(&mut ui, "1.5 ^", 1., 1).unwrap(), 1.5, 1e-12));
+    }
+
+    #[test]
+    fn funcs_not_returning_a_number_are_errors() {
+        let err = Some("expected the func to return a number".to_string());
+
+        for line in ["( [ ] ) 1 slope", "( pop [ ] ) [1 2] gradient", "( [ ] ) 1 2 nth_derivative"].iter() {
+            let mut ui = Ui::build();
+            ui.eval(line.to_string());
+            assert_eq!(ui.get_stack().get_error(), err.as_ref(), "{}", line);
+        }
+    }
+}
@@ -0,0 +1,484 @@
+use num_complex::Complex64;
+
+use crate::modes::*;
+use crate::modes::ops::matrix::*;
+
+impl Field for Complex64 {
+    fn zero() -> Self {Complex64::new(0., 0.)}
+    fn one() -> Self {Complex64::new(1., 0.)}
+    fn add(&self, other: &Self) -> Self {self + other}
+    fn sub(&self, other: &Self) -> Self {self - other}
+    fn mul(&self, other: &Self) -> Self {self * other}
+    fn div(&self, other: &Self) -> Self {self / other}
+    fn size(&self) -> f64 {self.norm()}
+    fn is_exact() -> bool {false}
+    fn to_item(&self) -> Item {complex_item(*self)}
+}
+
+// The routines below index matrices from 1, as in the EISPACK routines they
+// are based on.
+
+// scales rows and columns to make their norms similar, which improves the
+// accuracy of the eigenvalues
+#[allow(clippy::needless_range_loop)] // walks a row and a column together, as in EISPACK
+fn balance(a: &mut Matrix<f64>, n: usize) {
+    const RADIX: f64 = 2.;
+    let mut done = false;
+
+    while !done {
+        done = true;
+
+        for i in 1..=n {
+            let mut c = 0.;
+            let mut r = 0.;
+
+            for j in 1..=n {
+                if j != i {
+                    c += a[j][i].abs();
+                    r += a[i][j].abs();
+                }
+            }
+
+            if c == 0. || r == 0. {
+                continue;
+            }
+
+            let s = c + r;
+            let mut f = 1.;
+            let mut g = r / RADIX;
+
+            while c < g {
+                f *= RADIX;
+                c *= RADIX * RADIX;
+            }
+
+            g = r * RADIX;
+
+            while c > g {
+                f /= RADIX;
+                c /= RADIX * RADIX;
+            }
+
+            if (c + r) / f < 0.95 * s {
+                done = false;
+
+                for j in 1..=n {
+                    a[i][j] /= f;
+                    a[j][i] *= f;
+                }
+            }
+        }
+    }
+}
+
+// reduces a matrix to upper hessenberg form by elimination
+#[allow(clippy::needless_range_loop)] // index ranges follow the EISPACK routine
+fn hessenberg(a: &mut Matrix<f64>, n: usize) {
+    for m in 2..n {
+        let mut x: f64 = 0.;
+        let mut i = m;
+
+        for j in m..=n {
+            if a[j][m - 1].abs() > x.abs() {
+                x = a[j][m - 1];
+                i = j;
+            }
+        }
+
+        if i != m {
+            for j in m - 1..=n {
+                let tmp = a[i][j];
+                a[i][j] = a[m][j];
+                a[m][j] = tmp;
+            }
+            for row in a.iter_mut() {
+                row.swap(i, m);
+            }
+        }
+
+        if x != 0. {
+            for i in m + 1..=n {
+                let mut y = a[i][m - 1];
+
+                if y != 0. {
+                    y /= x;
+                    a[i][m - 1] = y;
+
+                    for j in m..=n {
+                        a[i][j] -= y * a[m][j];
+                    }
+                    for row in a.iter_mut() {
+                        row[m] += y * row[i];
+                    }
+                }
+            }
+        }
+    }
+
+    for (i, row) in a.iter_mut().enumerate() {
+        for x in row.iter_mut().take(i.saturating_sub(1)) {
+            *x = 0.;
+        }
+    }
+}
+
+fn sign(a: f64, b: f64) -> f64 {
+    if b >= 0. {a.abs()} else {-a.abs()}
+}
+
+// eigenvalues of an upper hessenberg matrix, using the shifted QR algorithm
+#[allow(clippy::needless_range_loop, clippy::many_single_char_names)] // a line by line port of EISPACK's hqr
+fn hessenberg_eigenvalues(a: &mut Matrix<f64>, n: usize) -> Result<Vec<Complex64>, String> {
+    let mut out = vec![Complex64::new(0., 0.); n + 1];
+    let mut anorm = 0.;
+
+    for i in 1..=n {
+        for j in (i - 1).max(1)..=n {
+            anorm += a[i][j].abs();
+        }
+    }
+
+    let mut nn = n;
+    let mut t = 0.;
+
+    while nn >= 1 {
+        let mut its = 0;
+
+        loop {
+            let mut l = nn;
+
+            while l >= 2 {
+                let mut s = a[l - 1][l - 1].abs() + a[l][l].abs();
+
+                if s == 0. {
+                    s = anorm;
+                }
+                if a[l][l - 1].abs() + s == s {
+                    a[l][l - 1] = 0.;
+                    break;
+                }
+
+                l -= 1;
+            }
+
+            let mut x = a[nn][nn];
+
+            if l == nn {
+                out[nn] = Complex64::new(x + t, 0.);
+                nn -= 1;
+            } else {
+                let mut y = a[nn - 1][nn - 1];
+                let mut w = a[nn][nn - 1] * a[nn - 1][nn];
+
+                if l == nn - 1 {
+                    let p = 0.5 * (y - x);
+                    let q = p * p + w;
+                    let mut z = q.abs().sqrt();
+
+                    x += t;
+
+                    if q >= 0. {
+                        z = p + sign(z, p);
+                        out[nn - 1] = Complex64::new(x + z, 0.);
+                        out[nn] = out[nn - 1];
+
+                        if z != 0. {
+                            out[nn] = Complex64::new(x - w / z, 0.);
+                        }
+                    } else {
+                        out[nn - 1] = Complex64::new(x + p, -z);
+                        out[nn] = Complex64::new(x + p, z);
+                    }
+
+                    nn -= 2;
+                } else {
+                    if its == 60 {
+                        return Err("eigenvalues did not converge".to_string());
+                    }
+
+                    if its == 10 || its == 20 {
+                        // exceptional shift
+                        t += x;
+
+                        for i in 1..=nn {
+                            a[i][i] -= x;
+                        }
+
+                        let s = a[nn][nn - 1].abs() + a[nn - 1][nn - 2].abs();
+
+                        x = 0.75 * s;
+                        y = x;
+                        w = -0.4375 * s * s;
+                    }
+
+                    its += 1;
+
+                    let mut m = nn - 2;
+                    let (mut p, mut q, mut r);
+
+                    loop {
+                        let z = a[m][m];
+                        r = x - z;
+                        let s = y - z;
+
+                        p = (r * s - w) / a[m + 1][m] + a[m][m + 1];
+                        q = a[m + 1][m + 1] - z - r - s;
+                        r = a[m + 2][m + 1];
+
+                        let s = p.abs() + q.abs() + r.abs();
+
+                        p /= s;
+                        q /= s;
+                        r /= s;
+
+                        if m == l {
+                            break;
+                        }
+
+                        let u = a[m][m - 1].abs() * (q.abs() + r.abs());
+                        let v = p.abs() * (a[m - 1][m - 1].abs() + z.abs() + a[m + 1][m + 1].abs());
+
+                        if u + v == v {
+                            break;
+                        }
+
+                        m -= 1;
+                    }
+
+                    for i in m + 2..=nn {
+                        a[i][i - 2] = 0.;
+
+                        if i != m + 2 {
+                            a[i][i - 3] = 0.;
+                        }
+                    }
+
+                    for k in m..nn {
+                        if k != m {
+                            p = a[k][k - 1];
+                            q = a[k + 1][k - 1];
+                            r = if k != nn - 1 {a[k + 2][k - 1]} else {0.};
+                            x = p.abs() + q.abs() + r.abs();
+
+                            if x != 0. {
+                                p /= x;
+                                q /= x;
+                                r /= x;
+                            }
+                        }
+
+                        let s = sign((p * p + q * q + r * r).sqrt(), p);
+
+                        if s == 0. {
+                            continue;
+                        }
+
+                        if k == m {
+                            if l != m {
+                                a[k][k - 1] = -a[k][k - 1];
+                            }
+                        } else {
+                            a[k][k - 1] = -s * x;
+                        }
+
+                        p += s;
+                        x = p / s;
+                        y = q / s;
+                        let z = r / s;
+                        q /= p;
+                        r /= p;
+
+                        for j in k..=nn {
+                            p = a[k][j] + q * a[k + 1][j];
+
+                            if k != nn - 1 {
+                                p += r * a[k + 2][j];
+                                a[k + 2][j] -= p * z;
+                            }
+
+                            a[k + 1][j] -= p * y;
+                            a[k][j] -= p * x;
+                        }
+
+                        for i in l..=nn.min(k + 3) {
+                            p = x * a[i][k] + y * a[i][k + 1];
+
+                            if k != nn - 1 {
+                                p += z * a[i][k + 2];
+                                a[i][k + 2] -= p * r;
+                            }
+
+                            a[i][k + 1] -= p * q;
+                            a[i][k] -= p;
+                        }
+                    }
+                }
+            }
+
+            if l + 1 >= nn {
+                break;
+            }
+        }
+    }
+
+    Ok(out.split_off(1))
+}
+
+// eigenvalues in order of decreasing real part, then decreasing imaginary part
+pub fn eigenvalues(m: &Matrix<f64>) -> Result<Vec<Complex64>, String> {
+    let n = m.len();
+
+    if m.iter().any(|r| r.len() != n) {
+        return Err(format!("expected a square matrix, got {}x{}", n, m[0].len()));
+    }
+
+    // pad the matrix so that it is indexed from 1
+    let mut a = vec![vec![0.; n + 1]; n + 1];
+
+    for (row, m_row) in a.iter_mut().skip(1).zip(m) {
+        row[1..].copy_from_slice(m_row);
+    }
+
+    balance(&mut a, n);
+    hessenberg(&mut a, n);
+
+    let mut out = hessenberg_eigenvalues(&mut a, n)?;
+
+    out.sort_by(|x, y| y.re.total_cmp(&x.re).then(y.im.total_cmp(&x.im)));
+
+    Ok(out)
+}
+
+fn normalize(v: &mut [Complex64]) {
+    let norm = v.iter().map(|x| x.norm_sqr()).sum::<f64>().sqrt();
+
+    // rotate so that the largest component is real and positive
+    let phase = v.iter()
+        .cloned()
+        .max_by(|x, y| x.norm().total_cmp(&y.norm()))
+        .map_or(Complex64::new(1., 0.), |x| x.conj() / x.norm());
+
+    for x in v.iter_mut() {
+        *x = *x * phase / norm;
+    }
+}
+
+fn remove_component(v: &mut [Complex64], u: &[Complex64]) {
+    let dot: Complex64 = u.iter().zip(v.iter()).map(|(u, v)| u.conj() * v).sum();
+
+    for (x, u) in v.iter_mut().zip(u) {
+        *x -= dot * u;
+    }
+}
+
+// Eigenvectors with unit length, found with inverse iteration. Vectors for
+// repeated eigenvalues are made orthogonal.
+pub fn eigenvectors(m: &Matrix<f64>, values: &[Complex64]) -> Result<Vec<Vec<Complex64>>, String> {
+    let n = m.len();
+    let scale = m.iter().flatten().map(|x| x.abs()).fold(0., f64::max).max(f64::MIN_POSITIVE);
+    let mut out: Vec<Vec<Complex64>> = Vec::new();
+
+    for (k, value) in values.iter().enumerate() {
+        let shift = value + Complex64::new(scale * 1e-12, scale * 1e-13);
+        let shifted: Matrix<Complex64> = (0..n)
+            .map(|i| (0..n).map(|j| {
+                let x = Complex64::new(m[i][j], 0.);
+                if i == j {x - shift} else {x}
+            }).collect())
+            .collect();
+
+        let same: Vec<usize> = (0..k)
+            .filter(|j| (values[*j] - value).norm() <= 1e-8 * scale)
+            .collect();
+
+        // starts from consecutive terms of frac(j / phi), which has no rational
+        // relations, so it is not orthogonal to eigenvectors with rational
+        // components, and moves on for each value, so that vectors for
+        // repeated eigenvalues start from different directions
+        let golden = (5f64.sqrt() - 1.) / 2.;
+        let mut v: Vec<Complex64> = (0..n)
+            .map(|i| Complex64::new(((k * n + i + 1) as f64 * golden).fract(), 0.))
+            .collect();
+
+        for _ in 0..3 {
+            for j in same.iter() {
+                remove_component(&mut v, &out[*j]);
+            }
+
+            normalize(&mut v);
+
+            let b: Matrix<Complex64> = v.iter().map(|x| vec![*x]).collect();
+            v = solve(&shifted, &b)?.into_iter().map(|r| r[0]).collect();
+        }
+
+        for j in same.iter() {
+            remove_component(&mut v, &out[*j]);
+        }
+
+        normalize(&mut v);
+
+        for x in v.iter_mut() {
+            if x.re.abs() < 1e-12 {x.re = 0.}
+            if x.im.abs() < 1e-12 {x.im = 0.}
+        }
+
+        out.push(v);
+    }
+
+    Ok(out)
+}
+
+// coefficients of det(xI - A), highest degree first, using the
+// Faddeev-LeVerrier algorithm
+pub fn char_poly<T: Field>(a: &Matrix<T>) -> Result<Vec<T>, String> {
+    let n = a.len();
+
+    if a.iter().any(|r| r.len() != n) {
+        return Err(format!("expected a square matrix, got {}x{}", n, a[0].len()));
+    }
+
+    let mut coeffs = vec![T::one()];
+    let mut m: Matrix<T> = vec![vec![T::zero(); n]; n];
+    let mut k_value = T::zero();
+
+    for _ in 1..=n {
+        k_value = k_value.add(&T::one());
+
+        for (i, row) in m.iter_mut().enumerate() {
+            row[i] = row[i].add(coeffs.last().unwrap());
+        }
+
+        m = matmul(a, &m)?;
+
+        let trace = (0..n).fold(T::zero(), |acc, i| acc.add(&m[i][i]));
+
+        coeffs.push(T::zero().sub(&trace.div(&k_value)));
+    }
+
+    Ok(coeffs)
+}
+
+pub fn eigenvalues_item(x: &Item) -> Result<Item, String> {
+    let m = to_float(to_matrix(x)?.0);
+
+    Ok(List(eigenvalues(&m)?.into_iter().map(complex_item).collect()))
+}
+
+pub fn eigenvectors_item(x: &Item) -> Result<Item, String> {
+    let m = to_float(to_matrix(x)?.0);
+    let values = eigenvalues(&m)?;
+    let vectors = eigenvectors(&m, &values)?;
+
+    Ok(List(
+        vectors.into_iter()
+            .map(|v| List(v.into_iter().map(complex_item).collect()))
+            .collect()
+    ))
+}
+
+pub fn char_poly_item(x: &Item) -> Result<Item, String> {
+    match to_matrix(x)?.0 {
+        Exact(m) => Ok(List(char_poly(&m)?.iter().map(Field::to_item).collect())),
+        Approx(m) => Ok(List(char_poly(&m)?.iter().map(Field::to_item).collect())),
+    }
+}
@@ -0,0 +1,244 @@
+use crate::modes::*;
+use crate::settings::from_radians;
+
+use num_complex::Complex64;
+use std::f64::consts::PI;
+
+fn to_complex_list(x: &Item) -> Result<Vec<Complex64>, String> {
+    match x {
+        List(l) => l.iter().map(Item::as_complex).collect::<Option<_>>(),
+        _ => None
+    }.ok_or_else(|| "expected a list of numbers".to_string())
+}
+
+fn is_real(x: &Item) -> bool {
+    match x {
+        List(l) => l.iter().all(|x| x.as_f64().is_some()),
+        _ => false
+    }
+}
+
+// iterative radix 2 transform, for lengths which are powers of two
+fn fft_pow2(a: &mut [Complex64], inverse: bool) {
+    let n = a.len();
+    let mut j = 0;
+
+    for i in 1..n {
+        let mut bit = n >> 1;
+
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+
+        if i < j {
+            a.swap(i, j);
+        }
+    }
+
+    let sign = if inverse {1.} else {-1.};
+    let mut len = 2;
+
+    while len <= n {
+        let w = Complex64::from_polar(1., sign * 2. * PI / len as f64);
+
+        for chunk in a.chunks_mut(len) {
+            let (lo, hi) = chunk.split_at_mut(len / 2);
+            let mut wk = Complex64::new(1., 0.);
+
+            for (x, y) in lo.iter_mut().zip(hi.iter_mut()) {
+                let t = *y * wk;
+
+                *y = *x - t;
+                *x += t;
+                wk *= w;
+            }
+        }
+
+        len <<= 1;
+    }
+}
+
+// the unnormalized discrete fourier transform, using bluestein's algorithm
+// to turn other lengths into a convolution of power of two length
+pub fn fft(a: &[Complex64], inverse: bool) -> Vec<Complex64> {
+    let n = a.len();
+
+    if n.is_power_of_two() || n == 0 {
+        let mut out = a.to_vec();
+        fft_pow2(&mut out, inverse);
+        return out;
+    }
+
+    let sign = if inverse {1.} else {-1.};
+    let chirp: Vec<Complex64> = (0..n)
+        .map(|k| Complex64::from_polar(1., sign * PI * ((k * k) % (2 * n)) as f64 / n as f64))
+        .collect();
+
+    let m = (2 * n - 1).next_power_of_two();
+    let mut x = vec![Complex64::new(0., 0.); m];
+    let mut y = vec![Complex64::new(0., 0.); m];
+
+    for k in 0..n {
+        x[k] = a[k] * chirp[k];
+        y[k] = chirp[k].conj();
+
+        if k > 0 {
+            y[m - k] = chirp[k].conj();
+        }
+    }
+
+    fft_pow2(&mut x, false);
+    fft_pow2(&mut y, false);
+
+    for (x, y) in x.iter_mut().zip(&y) {
+        *x *= y;
+    }
+
+    fft_pow2(&mut x, true);
+
+    (0..n).map(|k| x[k] * chirp[k] / m as f64).collect()
+}
+
+// parts smaller than the rounding error of the transform are set to zero, so
+// that real results come out as real numbers
+fn clean(x: Vec<Complex64>) -> Vec<Complex64> {
+    let scale = x.iter().fold(0., |acc: f64, z| acc.max(z.norm()));
+    let tol = 4. * (x.len() as f64 + 1.).log2() * f64::EPSILON * scale;
+    let snap = |x: f64| if x.abs() <= tol {0.} else {x};
+
+    x.into_iter().map(|z| Complex64::new(snap(z.re), snap(z.im))).collect()
+}
+
+fn from_complex_list(x: Vec<Complex64>) -> Item {
+    List(clean(x).into_iter().map(complex_item).collect())
+}
+
+pub fn fft_item(x: &Item) -> Result<Item, String> {
+    Ok(from_complex_list(fft(&to_complex_list(x)?, false)))
+}
+
+pub fn ifft_item(x: &Item) -> Result<Item, String> {
+    let x = to_complex_list(x)?;
+    let n = x.len() as f64;
+
+    Ok(from_complex_list(fft(&x, true).into_iter().map(|z| z / n).collect()))
+}
+
+pub fn magnitude_item(x: &Item) -> Result<Item, String> {
+    let out = clean(fft(&to_complex_list(x)?, false));
+    Ok(List(out.into_iter().map(|z| Num(z.norm())).collect()))
+}
+
+pub fn phase_item(x: &Item) -> Result<Item, String> {
+    let out = clean(fft(&to_complex_list(x)?, false));
+    Ok(List(out.into_iter().map(|z| Num(from_radians(z.arg()))).collect()))
+}
+
+// the full linear convolution, of length n + m - 1
+pub fn convolve(a: &[Complex64], b: &[Complex64]) -> Vec<Complex64> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+
+    let len = a.len() + b.len() - 1;
+    let m = len.next_power_of_two();
+    let pad = |x: &[Complex64]| {
+        let mut out = x.to_vec();
+        out.resize(m, Complex64::new(0., 0.));
+        out
+    };
+
+    let (mut x, mut y) = (pad(a), pad(b));
+
+    fft_pow2(&mut x, false);
+    fft_pow2(&mut y, false);
+
+    for (x, y) in x.iter_mut().zip(&y) {
+        *x *= y;
+    }
+
+    fft_pow2(&mut x, true);
+
+    x.into_iter().take(len).map(|z| z / m as f64).collect()
+}
+
+fn convolve_result(out: Vec<Complex64>, real: bool) -> Item {
+    if real {
+        List(clean(out).into_iter().map(|z| Num(z.re)).collect())
+    } else {
+        from_complex_list(out)
+    }
+}
+
+pub fn convolve_item(a: &Item, b: &Item) -> Result<Item, String> {
+    let out = convolve(&to_complex_list(a)?, &to_complex_list(b)?);
+    Ok(convolve_result(out, is_real(a) && is_real(b)))
+}
+
+// the cross correlation sum(a[n + k] * conj(b[n])), for each lag k from
+// 1 - len(b) to len(a) - 1
+pub fn correlate_item(a: &Item, b: &Item) -> Result<Item, String> {
+    let b_rev: Vec<Complex64> = to_complex_list(b)?.into_iter().rev().map(|z| z.conj()).collect();
+    let out = convolve(&to_complex_list(a)?, &b_rev);
+
+    Ok(convolve_result(out, is_real(a) && is_real(b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_dft(a: &[Complex64], inverse: bool) -> Vec<Complex64> {
+        let n = a.len();
+        let sign = if inverse {1.} else {-1.};
+
+        (0..n)
+            .map(|k| {
+                a.iter().enumerate()
+                    .map(|(j, x)| x * Complex64::from_polar(1., sign * 2. * PI * (j * k) as f64 / n as f64))
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn signal(n: usize) -> Vec<Complex64> {
+        (0..n).map(|k| Complex64::new((k as f64 * 0.7).sin() + 1., (k * k % 5) as f64 - 2.)).collect()
+    }
+
+    #[test]
+    fn fft_matches_a_naive_dft() {
+        for n in [1, 2, 3, 5, 6, 7, 8, 12, 17, 30, 64, 100].iter() {
+            let a = signal(*n);
+
+            for inverse in [false, true].iter() {
+                let fast = fft(&a, *inverse);
+                let slow = naive_dft(&a, *inverse);
+
+                assert_eq!(fast.len(), *n);
+
+                for (x, y) in fast.iter().zip(&slow) {
+                    assert!((x - y).norm() < 1e-9, "length {}: {} != {}", n, x, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn convolve_matches_a_naive_convolution() {
+        let (a, b) = (signal(7), signal(4));
+        let out = convolve(&a, &b);
+
+        assert_eq!(out.len(), 10);
+
+        for (k, x) in out.iter().enumerate() {
+            let y: Complex64 = (0..a.len())
+                .filter(|i| k >= *i && k - i < b.len())
+                .map(|i| a[i] * b[k - i])
+                .sum();
+
+            assert!((x - y).norm() < 1e-9, "{} != {}", x, y);
+        }
+    }
+}
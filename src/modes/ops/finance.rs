@@ -0,0 +1,244 @@
+use crate::modes::*;
+use crate::modes::ops::roots::brent_by;
+
+const MAX_PERIODS: f64 = 100000.;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Register {
+    N,
+    I,
+    Pv,
+    Pmt,
+    Fv,
+}
+
+pub use Register::*;
+
+// the time value of money registers, with i as a percentage per period and
+// money received positive and money paid negative. payments are made at the
+// end of each period, or at the start if begin is set.
+#[derive(Clone, Debug, Default)]
+pub struct Tvm {
+    pub n: f64,
+    pub i: f64,
+    pub pv: f64,
+    pub pmt: f64,
+    pub fv: f64,
+    pub begin: bool,
+}
+
+// (1 + r)^n - 1 and ((1 + r)^n - 1) / r, kept accurate for small rates
+fn growth(r: f64, n: f64) -> (f64, f64) {
+    let g = (n * r.ln_1p()).exp_m1();
+    (g, if r == 0. {n} else {g / r})
+}
+
+impl Tvm {
+    pub fn get(&self, reg: Register) -> f64 {
+        match reg {
+            N => self.n,
+            I => self.i,
+            Pv => self.pv,
+            Pmt => self.pmt,
+            Fv => self.fv,
+        }
+    }
+
+    pub fn set(&mut self, reg: Register, x: f64) {
+        match reg {
+            N => self.n = x,
+            I => self.i = x,
+            Pv => self.pv = x,
+            Pmt => self.pmt = x,
+            Fv => self.fv = x,
+        }
+    }
+
+    fn payment_scale(&self, r: f64) -> f64 {
+        if self.begin {1. + r} else {1.}
+    }
+
+    // the total value of every cash flow, moved to the end of the last period,
+    // which is zero when the registers agree
+    fn residual(&self, r: f64) -> f64 {
+        let (g, a) = growth(r, self.n);
+        self.pv * (g + 1.) + self.pmt * self.payment_scale(r) * a + self.fv
+    }
+
+    pub fn solve(&self, reg: Register) -> Result<f64, String> {
+        let r = self.i / 100.;
+        let (g, a) = growth(r, self.n);
+        let s = self.payment_scale(r);
+
+        let out = match reg {
+            N if r == 0. => -(self.pv + self.fv) / self.pmt,
+            N => {
+                let k = self.pmt * s / r;
+                ((k - self.fv) / (k + self.pv)).ln() / r.ln_1p()
+            }
+            // solving for ln(1 + r) keeps every guess above -100%
+            I => {
+                let err = || "no interest rate solves the tvm equation".to_string();
+
+                // with no periods, or no present value or payments, the
+                // residual is the same for every rate, so no one rate solves it
+                if self.n == 0. || (self.pv == 0. && self.pmt == 0.) {
+                    return Err(err());
+                }
+
+                let x = brent_by(|x| Ok(self.residual(x.exp_m1())), 0., 0.1)
+                    .map_err(|_| err())?;
+
+                x.exp_m1() * 100.
+            }
+            Pv => -(self.pmt * s * a + self.fv) / (g + 1.),
+            Pmt => -(self.pv * (g + 1.) + self.fv) / (s * a),
+            Fv => -(self.pv * (g + 1.) + self.pmt * s * a),
+        };
+
+        if out.is_finite() {
+            Ok(out)
+        } else {
+            Err("no value solves the tvm equation".to_string())
+        }
+    }
+
+    // rows of [period interest principal balance], with interest and principal
+    // having the same sign as the payment, and the balance after each payment
+    pub fn amortize(&self) -> Result<Vec<Vec<f64>>, String> {
+        let periods = self.n.round();
+        let r = self.i / 100.;
+
+        if !(1. ..=MAX_PERIODS).contains(&periods) {
+            return Err(format!("expected between 1 and {} periods", MAX_PERIODS));
+        }
+
+        let mut balance = self.pv;
+        let mut out = Vec::new();
+
+        for k in 1..=periods as usize {
+            // when paying at the start of each period, the first payment is
+            // made before any interest is due
+            let interest = if self.begin && k == 1 {0.} else {-balance * r};
+            let principal = self.pmt - interest;
+
+            balance += principal;
+            out.push(vec![k as f64, interest, principal, balance]);
+        }
+
+        Ok(out)
+    }
+}
+
+fn to_flows(x: &Item) -> Result<Vec<f64>, String> {
+    match x {
+        List(l) => l.iter().map(Item::as_f64).collect::<Option<_>>(),
+        _ => None
+    }.ok_or_else(|| "expected a list of cash flows".to_string())
+}
+
+fn num_arg(x: &Item, what: &str) -> Result<f64, String> {
+    x.as_f64().ok_or_else(|| format!("expected a number for the {}", what))
+}
+
+// the net present value of cash flows at the start of each period, beginning
+// with the present, discounted by a factor of e^(-x) per period
+fn npv_log(flows: &[f64], x: f64) -> f64 {
+    flows.iter().enumerate().map(|(k, cf)| cf * (-x * k as f64).exp()).sum()
+}
+
+pub fn npv_item(flows: &Item, rate: &Item) -> Result<Item, String> {
+    let flows = to_flows(flows)?;
+    let r = num_arg(rate, "rate")? / 100.;
+
+    Ok(Num(npv_log(&flows, r.ln_1p())))
+}
+
+pub fn irr_item(flows: &Item) -> Result<Item, String> {
+    let flows = to_flows(flows)?;
+
+    if !(flows.iter().any(|x| *x > 0.) && flows.iter().any(|x| *x < 0.)) {
+        return Err("expected both positive and negative cash flows".to_string());
+    }
+
+    let x = brent_by(|x| Ok(npv_log(&flows, x)), 0., 0.1)
+        .map_err(|_| "no internal rate of return was found".to_string())?;
+
+    Ok(Num(x.exp_m1() * 100.))
+}
+
+// converts a nominal annual rate compounded m times a year into the effective
+// annual rate, with an infinite m for continuous compounding
+pub fn effective_item(nominal: &Item, m: &Item) -> Result<Item, String> {
+    let j = num_arg(nominal, "rate")? / 100.;
+    let m = num_arg(m, "number of periods")?;
+
+    if m <= 0. {
+        return Err("expected a positive number of periods".to_string());
+    }
+
+    let x = if m.is_infinite() {j} else {m * (j / m).ln_1p()};
+    Ok(Num(x.exp_m1() * 100.))
+}
+
+pub fn nominal_item(effective: &Item, m: &Item) -> Result<Item, String> {
+    let e = num_arg(effective, "rate")? / 100.;
+    let m = num_arg(m, "number of periods")?;
+
+    if m <= 0. {
+        return Err("expected a positive number of periods".to_string());
+    }
+
+    let x = e.ln_1p();
+    Ok(Num(if m.is_infinite() {x} else {m * (x / m).exp_m1()} * 100.))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loan() -> Tvm {
+        Tvm {n: 360., i: 0.5, pv: 100000., pmt: -599.55, fv: 0., begin: false}
+    }
+
+    #[test]
+    fn solve_round_trips_every_register() {
+        for begin in [false, true].iter() {
+            let mut tvm = loan();
+            tvm.begin = *begin;
+            tvm.fv = tvm.solve(Fv).unwrap();
+
+            for reg in [N, I, Pv, Pmt, Fv].iter() {
+                let x = tvm.solve(*reg).unwrap();
+                let expected = tvm.get(*reg);
+
+                assert!((x - expected).abs() <= 1e-9 * expected.abs().max(1.), "{:?}: {} != {}", reg, x, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn solve_a_loan_payment() {
+        let pmt = loan().solve(Pmt).unwrap();
+        assert!((pmt + 599.55).abs() < 0.005);
+    }
+
+    #[test]
+    fn no_rate_solves_a_residual_independent_of_rate() {
+        let err = Err("no interest rate solves the tvm equation".to_string());
+
+        assert_eq!(Tvm {n: 10., ..Tvm::default()}.solve(I), err);
+        assert_eq!(Tvm {n: 0., pv: 100., fv: -100., ..Tvm::default()}.solve(I), err);
+        assert_eq!(Tvm {n: 10., fv: 100., ..Tvm::default()}.solve(I), err);
+    }
+
+    #[test]
+    fn storing_a_register_pops_the_number() {
+        let mut ui = crate::modes::Ui::build();
+        ui.eval("360 sto_n 0.5 sto_i 100000 sto_pv 0 sto_fv solve_pmt".to_string());
+
+        let stack = ui.get_stack();
+        assert_eq!(stack.len(), 1);
+        assert!((stack.last().and_then(Item::as_f64).unwrap() + 599.55).abs() < 0.005);
+    }
+}
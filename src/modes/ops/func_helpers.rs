@@ -2,6 +2,60 @@ use crate::modes::*;
 
 pub type FuncOp = Arc<dyn Fn(&mut Ui) + Send + Sync>;
 
+// runs a func on a separate stack holding only its arguments
+pub fn call_func(ui: &mut Ui, f: &str, args: Vec<Item>) -> Option<Item> {
+    let saved = mem::replace(ui.get_stack(), Stack::from_vec(args));
+    ui.eval(f.to_string());
+    let mut inner = mem::replace(ui.get_stack(), saved);
+
+    if let Some(e) = inner.get_error() {
+        ui.get_stack().set_error(e.clone());
+    }
+
+    inner.pop()
+}
+
+// runs a func on its own stack, returning a number or list of numbers
+pub fn call_func_nums(ui: &mut Ui, f: &str, args: Vec<Item>) -> Result<Vec<f64>, String> {
+    let out = match call_func(ui, f, args) {
+        Some(List(l)) => l.iter().map(Item::as_f64).collect(),
+        Some(x) => x.as_f64().map(|x| vec![x]),
+        None => None
+    };
+
+    out.ok_or_else(|| "expected the func to return a number or list of numbers".to_string())
+}
+
+// pops n arguments and pushes the results, restoring the arguments on failure
+pub fn results_func_op<F>(ui: &mut Ui, n: usize, f: F)
+    where F: FnOnce(&mut Ui, &[Item]) -> Result<Vec<Item>, String>
+{
+    let stack = ui.get_stack();
+
+    if stack.len() < n {
+        return;
+    }
+
+    let mut args: Vec<Item> = (0..n).map(|_| stack.pop().unwrap()).collect();
+    args.reverse();
+
+    match f(ui, &args) {
+        Ok(out) => {
+            for i in out {
+                ui.get_stack().push(i);
+            }
+        }
+        Err(e) => {
+            let stack = ui.get_stack();
+
+            for i in args {
+                stack.push(i);
+            }
+            stack.set_error(e);
+        }
+    }
+}
+
 fn solver<F>(op: &'static F, start: f64, end: f64) -> FuncOp
     where F: Fn(f64, f64, f64) -> bool + Send + Sync
 {
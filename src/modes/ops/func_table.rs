@@ -1,5 +1,10 @@
 use crate::modes::*;
 pub use crate::modes::ops::func_helpers::*;
+use crate::modes::ops::ode::*;
+use crate::modes::ops::quadrature::*;
+use crate::modes::ops::autodiff::*;
+use crate::modes::ops::roots::*;
+use crate::modes::ops::minimize::*;
 
 fn run(ui: &mut Ui) {
     let stack = ui.get_stack();
@@ -22,104 +27,6 @@ fn run_times(ui: &mut Ui) {
     }
 }
 
-fn integrate_rects(ui: &mut Ui) {
-    let mut stack = ui.get_stack();
-
-    let rect = if let Some(n) = stack.pop_as_num () {n as usize} else {return};
-    let high = if let Some(n) = stack.pop_as_num () {n} else {return};
-    let low  = if let Some(n) = stack.pop_as_num () {n} else {return};
-    let f    = if let Some(f) = stack.pop_as_func() {f} else {return};
-
-    let mut num = low;
-    let delta = (high - low) / rect as f64;
-
-    let mut multipliers = Vec::new();
-    let mut inputs = Vec::new();
-
-    for i in 0..rect+1 {
-        if i == 0 || i == rect {
-            multipliers.push(Num(1.));
-        } else {
-            multipliers.push(Num(((i % 2 + 1) * 2) as f64));
-        }
-
-        inputs.push(Num(num));
-
-        num += delta;
-    }
-
-    stack.push(List(multipliers));
-    stack.push(List(inputs));
-
-    ui.eval(f);
-    ui.eval("* sum_list".to_string());
-
-    stack = ui.get_stack();
-
-    let n = stack.pop_as_num().unwrap();
-    stack.push(Num(n * (high - low) / (3. * rect as f64)));
-}
-
-fn integrate(ui: &mut Ui) {
-    ui.get_stack().push(Num(100000.));
-    integrate_rects(ui);
-}
-
-fn euler_approx(log: bool, ui: &mut Ui) {
-    let stack = ui.get_stack();
-
-    let steps = if let Some(n) = stack.pop_as_num () {n as usize} else {return};
-    let endx  = if let Some(n) = stack.pop_as_num () {n} else {return};
-    let start = if let Some(l) = stack.pop_as_list() {l} else {return};
-    let func  = if let Some(f) = stack.pop_as_func() {f} else {return};
-
-    let mut start = Stack::from_vec(start);
-
-    let mut y = if let Some(n) = start.pop_as_num() {n} else {return};
-    let mut x = if let Some(n) = start.pop_as_num() {n} else {return};
-
-    let delta = (endx - x) / steps as f64;
-    let mut out = Stack::new();
-
-    for _ in 0..steps {
-        ui.get_stack().push(Num(x));
-        ui.get_stack().push(Num(y));
-        ui.eval(func.clone());
-        let slope = ui.get_stack().pop_as_num().unwrap();
-
-        if log {
-            out.push(List(vec![Num(x), Num(y), Num(slope)]));
-        }
-        x += delta;
-        y += slope * delta;
-    }
-
-    if log {
-        ui.get_stack().push(List(out.into_vec()));
-    }
-    ui.get_stack().push(List(vec![Num(x), Num(y)]));
-}
-
-fn derivative_at(ui: &mut Ui) {
-    let stack = ui.get_stack();
-
-    let x = if let Some(n) = stack.pop_as_num () {n} else {return};
-    let f = if let Some(f) = stack.pop_as_func() {f} else {return};
-
-    let d = (f64::EPSILON * 1000000000. * x).max(f64::EPSILON);
-    let x2 = x + d;
-
-    ui.get_stack().push(Num(x));
-    ui.eval(f.clone());
-    let y = ui.get_stack().pop_as_num().unwrap();
-
-    ui.get_stack().push(Num(x2));
-    ui.eval(f);
-    let y2 = ui.get_stack().pop_as_num().unwrap();
-
-    ui.get_stack().push(Num((y2 - y) / d));
-}
-
 fn sequence(ui: &mut Ui) {
     let stack = ui.get_stack();
 
@@ -148,15 +55,29 @@ pub fn gen_func_ops() -> Vec<(String, Vec<Vec<Input>>, FuncOp)> {
 
         ("max",        vec!["irx" ], Arc::new(|ui| optimize(true, ui))),
         ("min",        vec!["irn" ], Arc::new(|ui| optimize(false, ui))),
+        ("nelder_mead", vec!["ird"], Arc::new(|ui| results_func_op(ui, 2, nelder_mead_item))),
+        ("bounded_min", vec!["irg"], Arc::new(|ui| results_func_op(ui, 3, bounded_min_item))),
         ("zero",       vec!["irz" ], range_solver(&zero)),
+        ("newton",        vec!["irt"], Arc::new(|ui| results_func_op(ui, 2, newton_item))),
+        ("secant",        vec!["irs"], Arc::new(|ui| results_func_op(ui, 3, secant_item))),
+        ("brent",         vec!["irb"], Arc::new(|ui| results_func_op(ui, 3, brent_item))),
+        ("newton_system", vec!["irm"], Arc::new(|ui| results_func_op(ui, 2, newton_system_item))),
 
         ("sequence", vec!["ifq"], Arc::new(sequence)),
 
-        ("area",  vec!["ifa"], Arc::new(integrate)),
-        ("slope", vec!["ifs"], Arc::new(derivative_at)),
-
-        ("euler",        vec!["ife"] , Arc::new(|ui| euler_approx(false, ui))),
-        ("euler_log",    vec!["ifle"], Arc::new(|ui| euler_approx(true, ui))),
+        ("area",     vec!["ifa"], Arc::new(|ui| results_func_op(ui, 3, area))),
+        ("integral", vec!["ifg"], Arc::new(|ui| results_func_op(ui, 4, integral))),
+        ("slope",          vec!["ifs" ], Arc::new(|ui| results_func_op(ui, 2, slope_item))),
+        ("nth_derivative", vec!["ifns"], Arc::new(|ui| results_func_op(ui, 3, nth_derivative_item))),
+        ("gradient",       vec!["ifv" ], Arc::new(|ui| results_func_op(ui, 2, gradient_item))),
+        ("jacobian",       vec!["ifj" ], Arc::new(|ui| results_func_op(ui, 2, jacobian_item))),
+
+        ("euler",        vec!["ife"] , Arc::new(|ui| ode_solve(Euler, false, ui))),
+        ("euler_log",    vec!["ifle"], Arc::new(|ui| ode_solve(Euler, true, ui))),
+        ("rk4",          vec!["ifk"] , Arc::new(|ui| ode_solve(Rk4, false, ui))),
+        ("rk4_log",      vec!["iflk"], Arc::new(|ui| ode_solve(Rk4, true, ui))),
+        ("rk45",         vec!["ifp"] , Arc::new(|ui| ode_solve(Rk45, false, ui))),
+        ("rk45_log",     vec!["iflp"], Arc::new(|ui| ode_solve(Rk45, true, ui))),
 
         ("map_depth",  vec!["ifdm"], Arc::new(map_depth)),
         ("map"      ,  vec!["ifm" ], Arc::new(|ui: &mut Ui| {ui.get_stack().push(Num(0.)); map_depth(ui)})),
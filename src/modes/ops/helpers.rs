@@ -1,4 +1,7 @@
 use crate::modes::*;
+use num_rational::BigRational;
+use num_complex::Complex64;
+use num_traits::{Signed, ToPrimitive};
 
 pub type Op = Box<dyn Fn(&mut Stack) + Sync + Send>;
 
@@ -6,26 +9,399 @@ pub fn basic<T>(f: &'static (impl Fn(&mut Stack) -> T + Sync + Send)) -> Op {
     Box::new(move |stack: &mut Stack| {f(stack);})
 }
 
+// Operators on real numbers, which convert dimensionless quantities and
+// report an error for complex numbers and quantities with units
 pub fn op_1(f: &'static (impl Fn(f64) -> f64 + Sync + Send)) -> Op {
+    num_op_1(f, None, None, Dimensionless)
+}
+
+pub fn op_2(f: &'static (impl Fn(f64, f64) -> f64 + Sync + Send)) -> Op {
+    num_op_2(f, None, None, Dimensionless)
+}
+
+pub fn op_3(f: &'static (impl Fn(f64, f64, f64) -> f64 + Sync + Send)) -> Op {
     Box::new(move |stack: &mut Stack| {
-        if stack.is_empty() {
+        if stack.len() < 3 {
             return;
         }
 
         let mut new_stack = Stack::new();
         new_stack.push(stack.pop().unwrap());
-        let g: Box<dyn Fn(Vec<f64>) -> Item> = Box::new(move |s| {
-            if !s.is_empty() {
-                Num(f(s[0]))
+        new_stack.push(stack.pop().unwrap());
+        new_stack.push(stack.pop().unwrap());
+        new_stack.reverse();
+
+        let error = Cell::new(None);
+        let args = new_stack.clone();
+
+        let k = |s: Vec<Item>| {
+            let s: Result<Vec<f64>, String> = s.into_iter()
+                .map(|x| {
+                    let (x, _) = apply_rule_1(Dimensionless, x)?;
+                    real_arg(&x)
+                })
+                .collect();
+
+            match s {
+                Ok(s) if s.len() >= 3 => Num(f(s[0], s[1], s[2])),
+                Ok(_) => Num(f64::NAN),
+                Err(e) => {
+                    error.set(Some(e));
+                    Num(f64::NAN)
+                }
+            }
+        };
+
+        let out = new_stack.apply_map_items(&k);
+
+        match error.take() {
+            Some(e) => {
+                for i in args {
+                    stack.push(i);
+                }
+                stack.set_error(e);
+            }
+            None => stack.push(out)
+        }
+    })
+}
+
+fn real_arg(x: &Item) -> Result<f64, String> {
+    match x {
+        Complex(_) => Err("expected real numbers".to_string()),
+        _ => Ok(x.as_f64().unwrap_or(f64::NAN))
+    }
+}
+
+type RealFn1 = dyn Fn(f64) -> f64 + Sync + Send;
+type RealFn2 = dyn Fn(f64, f64) -> f64 + Sync + Send;
+type ExactFn1 = dyn Fn(&BigRational) -> Option<BigRational> + Sync + Send;
+type ExactFn2 = dyn Fn(&BigRational, &BigRational) -> Option<BigRational> + Sync + Send;
+type ComplexFn1 = dyn Fn(Complex64) -> Complex64 + Sync + Send;
+type ComplexFn2 = dyn Fn(Complex64, Complex64) -> Complex64 + Sync + Send;
+
+// Complex inputs use the complex function, rationals and words use the exact
+// function when it returns a value, and everything else uses the real
+// function. Exact results involving a word are truncated and wrapped to the
+// size of that word. A real function returning NaN for a non-NaN input is
+// retried in the complex plane, and complex inputs without a complex function
+// are an error.
+fn eval_1(x: &Item,
+          f: &RealFn1,
+          g: Option<&ExactFn1>,
+          h: Option<&ComplexFn1>) -> Result<Item, String>
+{
+    if let Complex(z) = x {
+        return h.map(|h| complex_item(h(*z))).ok_or_else(|| "expected a real number".to_string());
+    }
+
+    if let (Rational(r), Some(g)) = (x, g) {
+        if let Some(out) = g(r) {
+            return Ok(Rational(out));
+        }
+    }
+
+    if let (Integer(w), Some(g)) = (x, g) {
+        if let Some(out) = g(&x.as_rational().unwrap()) {
+            return Ok(Integer(w.with_int(&out.trunc().to_integer())));
+        }
+    }
+
+    let x = if let Some(x) = x.as_f64() {x} else {return Ok(Num(f64::NAN))};
+    let out = f(x);
+
+    Ok(match h {
+        Some(h) if out.is_nan() && !x.is_nan() =>
+            complex_item(h(Complex64::from(x))),
+        _ => Num(out)
+    })
+}
+
+fn eval_2(x: &Item,
+          y: &Item,
+          f: &RealFn2,
+          g: Option<&ExactFn2>,
+          h: Option<&ComplexFn2>) -> Result<Item, String>
+{
+    if let (Complex(_), _) | (_, Complex(_)) = (x, y) {
+        return match (h, x.as_complex(), y.as_complex()) {
+            (Some(h), Some(x), Some(y)) => Ok(complex_item(h(x, y))),
+            (Some(_), _, _) => Ok(Num(f64::NAN)),
+            (None, _, _) => Err("expected real numbers".to_string())
+        };
+    }
+
+    if let (Rational(x), Rational(y), Some(g)) = (x, y, g) {
+        if let Some(out) = g(x, y) {
+            return Ok(Rational(out));
+        }
+    }
+
+    let word = [x, y].iter().find_map(|i| if let Integer(w) = i {Some(*w)} else {None});
+
+    if let (Some(w), Some(g)) = (word, g) {
+        if let (Some(x), Some(y)) = (x.as_rational(), y.as_rational()) {
+            if let Some(out) = g(&x, &y) {
+                return Ok(Integer(w.with_int(&out.trunc().to_integer())));
+            }
+        }
+    }
+
+    let (x, y) =
+        match (x.as_f64(), y.as_f64()) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return Ok(Num(f64::NAN))
+        };
+    let out = f(x, y);
+
+    Ok(match h {
+        Some(h) if out.is_nan() && !x.is_nan() && !y.is_nan() =>
+            complex_item(h(Complex64::from(x), Complex64::from(y))),
+        _ => Num(out)
+    })
+}
+
+fn unit_error(rule: UnitRule, u1: &Units, u2: &Units) -> String {
+    if u2.is_empty() {
+        format!("incompatible units: {} and a number", u1)
+    } else if u1.is_empty() {
+        format!("incompatible units: a number and {}", u2)
+    } else if rule == Dimensionless {
+        format!("expected dimensionless arguments, got {} and {}", u1, u2)
+    } else {
+        format!("incompatible units: {} and {}", u1, u2)
+    }
+}
+
+// Removes the units from x, returning the units of the result
+fn apply_rule_1(rule: UnitRule, x: Item) -> Result<(Item, Units), String> {
+    let (x, u) = x.split_units();
+
+    match rule {
+        Same => Ok((x, u)),
+        Power(num, den) => {
+            u.pow(num, den)
+                .map(|out| (x, out))
+                .ok_or_else(|| format!("cannot raise {} to the power {}/{}", u, num, den))
+        }
+        _ => {
+            if u.dim() == Units::new().dim() {
+                Ok((scale_item(x, u.scale()), Units::new()))
+            } else {
+                Err(format!("expected a dimensionless argument, got {}", u))
+            }
+        }
+    }
+}
+
+fn apply_rule_2(rule: UnitRule, x: Item, y: Item)
+    -> Result<(Item, Item, Units), String>
+{
+    let (x, u1) = x.split_units();
+    let (y, u2) = y.split_units();
+    let dimensionless = Units::new().dim();
+
+    match rule {
+        Same => {
+            if u1.dim() == u2.dim() {
+                Ok((x, scale_item(y, u2.scale() / u1.scale()), u1))
             } else {
-                Num(std::f64::NAN)
+                Err(unit_error(rule, &u1, &u2))
+            }
+        }
+        Multiply => Ok((x, y, u1.mul(&u2))),
+        Divide => Ok((x, y, u1.mul(&u2.pow(-1, 1).unwrap()))),
+        Exponent | Root if u2.dim() == dimensionless => {
+            let y = scale_item(y, u2.scale());
+
+            if u1.is_empty() {
+                return Ok((x, y, u1));
+            }
+
+            let exp = y.as_rational()
+                .map(|r| (r.numer().to_i32(), r.denom().to_i32()));
+
+            let out =
+                match (rule, exp) {
+                    (Exponent, Some((Some(n), Some(d)))) => u1.pow(n, d),
+                    (Root, Some((Some(n), Some(d)))) => u1.pow(d, n),
+                    _ => None
+                };
+
+            match out {
+                Some(out) => Ok((x, y, out)),
+                None => Err(format!("cannot raise {} to the power {}", u1, y))
+            }
+        }
+        _ => {
+            if u1.dim() == dimensionless && u2.dim() == dimensionless {
+                Ok((scale_item(x, u1.scale()), scale_item(y, u2.scale()), Units::new()))
+            } else {
+                Err(unit_error(Dimensionless, &u1, &u2))
+            }
+        }
+    }
+}
+
+// If the operator fails for any element, its arguments are put back on the
+// stack and the error is reported.
+fn num_op_1(f: &'static RealFn1,
+            g: Option<&'static ExactFn1>,
+            h: Option<&'static ComplexFn1>,
+            rule: UnitRule) -> Op
+{
+    Box::new(move |stack: &mut Stack| {
+        if stack.is_empty() {
+            return;
+        }
+
+        let mut new_stack = Stack::new();
+        new_stack.push(stack.pop().unwrap());
+
+        let error = Cell::new(None);
+        let args = new_stack.clone();
+
+        let k = |s: Vec<Item>| {
+            let x = if let Some(x) = s.into_iter().next() {x} else {return Num(f64::NAN)};
+
+            match apply_rule_1(rule, x).and_then(|(x, u)| Ok(quantity_item(eval_1(&x, f, g, h)?, u))) {
+                Ok(out) => out,
+                Err(e) => {
+                    error.set(Some(e));
+                    Num(f64::NAN)
+                }
+            }
+        };
+
+        let out = new_stack.apply_map_items(&k);
+
+        match error.take() {
+            Some(e) => {
+                for i in args {
+                    stack.push(i);
+                }
+                stack.set_error(e);
+            }
+            None => stack.push(out)
+        }
+    })
+}
+
+fn num_op_2(f: &'static RealFn2,
+            g: Option<&'static ExactFn2>,
+            h: Option<&'static ComplexFn2>,
+            rule: UnitRule) -> Op
+{
+    Box::new(move |stack: &mut Stack| {
+        if stack.len() < 2 {
+            return;
+        }
+
+        let mut new_stack = Stack::new();
+        new_stack.push(stack.pop().unwrap());
+        new_stack.push(stack.pop().unwrap());
+        new_stack.reverse();
+
+        let error = Cell::new(None);
+        let args = new_stack.clone();
+
+        let k = |s: Vec<Item>| {
+            let mut s = s.into_iter();
+
+            let (x, y) =
+                match (s.next(), s.next()) {
+                    (Some(x), Some(y)) => (x, y),
+                    _ => return Num(f64::NAN)
+                };
+
+            match apply_rule_2(rule, x, y).and_then(|(x, y, u)| Ok(quantity_item(eval_2(&x, &y, f, g, h)?, u))) {
+                Ok(out) => out,
+                Err(e) => {
+                    error.set(Some(e));
+                    Num(f64::NAN)
+                }
+            }
+        };
+
+        let out = new_stack.apply_map_items(&k);
+
+        match error.take() {
+            Some(e) => {
+                for i in args {
+                    stack.push(i);
+                }
+                stack.set_error(e);
+            }
+            None => stack.push(out)
+        }
+    })
+}
+
+pub fn exact_op_1(
+    f: &'static (impl Fn(f64) -> f64 + Sync + Send),
+    g: &'static (impl Fn(&BigRational) -> Option<BigRational> + Sync + Send)
+) -> Op {
+    num_op_1(f, Some(g), None, Dimensionless)
+}
+
+pub fn exact_op_2(
+    f: &'static (impl Fn(f64, f64) -> f64 + Sync + Send),
+    g: &'static (impl Fn(&BigRational, &BigRational) -> Option<BigRational> + Sync + Send)
+) -> Op {
+    num_op_2(f, Some(g), None, Dimensionless)
+}
+
+pub fn complex_op_1(
+    f: &'static (impl Fn(f64) -> f64 + Sync + Send),
+    h: &'static (impl Fn(Complex64) -> Complex64 + Sync + Send)
+) -> Op {
+    num_op_1(f, None, Some(h), Dimensionless)
+}
+
+pub fn complex_op_2(
+    f: &'static (impl Fn(f64, f64) -> f64 + Sync + Send),
+    h: &'static (impl Fn(Complex64, Complex64) -> Complex64 + Sync + Send)
+) -> Op {
+    num_op_2(f, None, Some(h), Dimensionless)
+}
+
+pub fn unit_op_1(
+    rule: UnitRule,
+    f: &'static (impl Fn(f64) -> f64 + Sync + Send),
+    g: &'static (impl Fn(&BigRational) -> Option<BigRational> + Sync + Send),
+    h: &'static (impl Fn(Complex64) -> Complex64 + Sync + Send)
+) -> Op {
+    num_op_1(f, Some(g), Some(h), rule)
+}
+
+pub fn unit_op_2(
+    rule: UnitRule,
+    f: &'static (impl Fn(f64, f64) -> f64 + Sync + Send),
+    g: &'static (impl Fn(&BigRational, &BigRational) -> Option<BigRational> + Sync + Send),
+    h: &'static (impl Fn(Complex64, Complex64) -> Complex64 + Sync + Send)
+) -> Op {
+    num_op_2(f, Some(g), Some(h), rule)
+}
+
+pub fn item_op_1(f: &'static (impl Fn(Item) -> Item + Sync + Send)) -> Op {
+    Box::new(move |stack: &mut Stack| {
+        if stack.is_empty() {
+            return;
+        }
+
+        let mut new_stack = Stack::new();
+        new_stack.push(stack.pop().unwrap());
+        let g: Box<dyn Fn(Vec<Item>) -> Item> = Box::new(move |s| {
+            match s.into_iter().next() {
+                Some(x) => f(x),
+                None => Num(f64::NAN)
             }
         });
-        stack.push(new_stack.apply_map(&g));
+        stack.push(new_stack.apply_map_items(&g));
     })
 }
 
-pub fn op_2(f: &'static (impl Fn(f64, f64) -> f64 + Sync + Send)) -> Op {
+pub fn item_op_2(f: &'static (impl Fn(Item, Item) -> Item + Sync + Send)) -> Op {
     Box::new(move |stack: &mut Stack| {
         if stack.len() < 2 {
             return;
@@ -36,16 +412,147 @@ pub fn op_2(f: &'static (impl Fn(f64, f64) -> f64 + Sync + Send)) -> Op {
         new_stack.push(stack.pop().unwrap());
         new_stack.reverse();
 
-        let g: Box<dyn Fn(Vec<f64>) -> Item> = Box::new(move |s| {
-            if s.len() >= 2 {
-                Num(f(s[0], s[1]))
-            } else {
-                Num(std::f64::NAN)
+        let g: Box<dyn Fn(Vec<Item>) -> Item> = Box::new(move |s| {
+            let mut s = s.into_iter();
+
+            match (s.next(), s.next()) {
+                (Some(x), Some(y)) => f(x, y),
+                _ => Num(f64::NAN)
             }
         });
+        stack.push(new_stack.apply_map_items(&g));
+    })
+}
 
+pub fn item_op_3(f: &'static (impl Fn(Item, Item, Item) -> Item + Sync + Send)) -> Op {
+    Box::new(move |stack: &mut Stack| {
+        if stack.len() < 3 {
+            return;
+        }
 
-        stack.push(new_stack.apply_map(&g));
+        let mut new_stack = Stack::new();
+        new_stack.push(stack.pop().unwrap());
+        new_stack.push(stack.pop().unwrap());
+        new_stack.push(stack.pop().unwrap());
+        new_stack.reverse();
+
+        let g: Box<dyn Fn(Vec<Item>) -> Item> = Box::new(move |s| {
+            let mut s = s.into_iter();
+
+            match (s.next(), s.next(), s.next()) {
+                (Some(x), Some(y), Some(z)) => f(x, y, z),
+                _ => Num(f64::NAN)
+            }
+        });
+        stack.push(new_stack.apply_map_items(&g));
+    })
+}
+
+// Plain integers are treated as 64 bit signed words, and only stay words if
+// another input was a word.
+pub fn word_op_1(f: &'static (impl Fn(Word) -> Word + Sync + Send)) -> Op {
+    Box::new(move |stack: &mut Stack| {
+        if stack.is_empty() {
+            return;
+        }
+
+        let mut new_stack = Stack::new();
+        new_stack.push(stack.pop().unwrap());
+        let g: Box<dyn Fn(Vec<Item>) -> Item> = Box::new(move |s| {
+            match s.first() {
+                Some(Integer(w)) => Integer(f(*w)),
+                Some(x) => match x.as_word() {
+                    Some(w) => Rational(f(w).to_bigint().into()),
+                    None => Num(f64::NAN)
+                },
+                None => Num(f64::NAN)
+            }
+        });
+        stack.push(new_stack.apply_map_items(&g));
+    })
+}
+
+// The second word is converted to the size of the first
+pub fn word_op_2(f: &'static (impl Fn(Word, Word) -> Word + Sync + Send)) -> Op {
+    Box::new(move |stack: &mut Stack| {
+        if stack.len() < 2 {
+            return;
+        }
+
+        let mut new_stack = Stack::new();
+        new_stack.push(stack.pop().unwrap());
+        new_stack.push(stack.pop().unwrap());
+        new_stack.reverse();
+
+        let g: Box<dyn Fn(Vec<Item>) -> Item> = Box::new(move |s| {
+            if s.len() < 2 {
+                return Num(f64::NAN);
+            }
+
+            let (x, y) =
+                match (s[0].as_word(), s[1].as_word()) {
+                    (Some(x), Some(y)) => (x, y),
+                    _ => return Num(f64::NAN)
+                };
+
+            match s.iter().find_map(|i| if let Integer(w) = i {Some(*w)} else {None}) {
+                Some(w) => {
+                    let x = w.with_int(&x.to_bigint());
+                    let y = w.with_int(&y.to_bigint());
+
+                    Integer(f(x, y))
+                }
+                None => Rational(f(x, y).to_bigint().into())
+            }
+        });
+        stack.push(new_stack.apply_map_items(&g));
+    })
+}
+
+// reads an item as a count of bits, which is never wrapped to a word's size
+pub fn as_count(x: &Item) -> Option<i128> {
+    match x {
+        Integer(w) => Some(w.to_i128()),
+        Rational(r) => {
+            let n = r.trunc().to_integer();
+            Some(n.to_i128().unwrap_or(if n.is_negative() {i128::MIN} else {i128::MAX}))
+        }
+        // float to int casts saturate
+        Num(n) if !n.is_nan() => Some(*n as i128),
+        _ => None
+    }
+}
+
+// Like word_op_2, but the second argument is a count, such as the number of
+// bits to shift by, which keeps its value instead of being converted
+pub fn word_count_op(f: &'static (impl Fn(Word, i128) -> Word + Sync + Send)) -> Op {
+    Box::new(move |stack: &mut Stack| {
+        if stack.len() < 2 {
+            return;
+        }
+
+        let mut new_stack = Stack::new();
+        new_stack.push(stack.pop().unwrap());
+        new_stack.push(stack.pop().unwrap());
+        new_stack.reverse();
+
+        let g: Box<dyn Fn(Vec<Item>) -> Item> = Box::new(move |s| {
+            if s.len() < 2 {
+                return Num(f64::NAN);
+            }
+
+            let (x, n) =
+                match (s[0].as_word(), as_count(&s[1])) {
+                    (Some(x), Some(n)) => (x, n),
+                    _ => return Num(f64::NAN)
+                };
+
+            match s.iter().find_map(|i| if let Integer(w) = i {Some(*w)} else {None}) {
+                Some(w) => Integer(f(w.with_int(&x.to_bigint()), n)),
+                None => Rational(f(x, n).to_bigint().into())
+            }
+        });
+        stack.push(new_stack.apply_map_items(&g));
     })
 }
 
@@ -59,7 +566,7 @@ pub fn vec2_op(f: &'static (impl Fn(f64, f64) -> (f64, f64) + Sync + Send)) -> O
         let i2 = stack.pop().unwrap();
         let i1 = stack.pop().unwrap();
 
-        if let (Num(i1), Num(i2)) = (i1, i2) {
+        if let (Some(i1), Some(i2)) = (i1.as_f64(), i2.as_f64()) {
             let (o1, o2) = f(i1, i2);
 
             stack.push(Num(o1));
@@ -70,25 +577,53 @@ pub fn vec2_op(f: &'static (impl Fn(f64, f64) -> (f64, f64) + Sync + Send)) -> O
     })
 }
 
-pub fn fold_op(f: &'static (impl Fn(f64, f64) -> f64 + Sync + Send), start: f64) -> Op {
-    Box::new(move |stack: &mut Stack| {
-        let n = stack.apply_fold(f, start);
+// applies a binary operator to two items
+pub fn apply_2(f: fn(&mut Stack), x: Item, y: Item) -> Result<Item, String> {
+    let mut tmp_stack = Stack::from_vec(vec![x, y]);
+    f(&mut tmp_stack);
+
+    match tmp_stack.get_error() {
+        Some(e) => Err(e.clone()),
+        None => Ok(tmp_stack.pop().unwrap())
+    }
+}
 
-        stack.clear();
-        stack.push(Num(n));
+// combines every number in the items and any lists in them with a binary
+// operator, starting from the first one
+fn fold_items(items: &[Item], f: fn(&mut Stack), state: Option<Item>) -> Result<Option<Item>, String> {
+    items.iter().try_fold(state, |state, x| match (x, state) {
+        (Func(_), state) => Ok(state),
+        (List(l), state) => fold_items(l, f, state),
+        (x, None) => Ok(Some(x.clone())),
+        (x, Some(state)) => apply_2(f, state, x.clone()).map(Some)
     })
 }
 
-pub fn list_fold_op(f: &'static (impl Fn(f64, f64) -> f64 + Sync + Send), start: f64) -> Op {
+// folds the whole stack into one item, which is empty for an empty stack
+pub fn fold_op(f: fn(&mut Stack), empty: i64) -> Op {
     Box::new(move |stack: &mut Stack| {
-        match stack.pop() {
-            Some(List(s)) => {
-                let n = Stack::apply_fold_vec(&s, f, start);
+        let items = stack.clone().into_vec();
+
+        match fold_items(&items, f, None) {
+            Ok(out) => {
+                stack.clear();
+                stack.push(out.unwrap_or_else(|| Item::exact(empty)));
+            }
+            Err(e) => stack.set_error(e)
+        }
+    })
+}
 
-                stack.push(Num(n));
-            },
-            Some(i) => stack.push(i),
-            None => {}
+pub fn list_fold_op(f: fn(&mut Stack), empty: i64) -> Op {
+    Box::new(move |stack: &mut Stack| {
+        if let Some(List(l)) = stack.last() {
+            match fold_items(l, f, None) {
+                Ok(out) => {
+                    stack.pop();
+                    stack.push(out.unwrap_or_else(|| Item::exact(empty)));
+                }
+                Err(e) => stack.set_error(e)
+            }
         }
     })
 }
@@ -106,3 +641,139 @@ pub fn chain(fs: Vec<Op>) -> Op {
         }
     })
 }
+
+// the numbers in a list, flattened in the same way as the list folds
+pub fn list_nums(v: &[Item]) -> Vec<f64> {
+    let mut out = Vec::new();
+
+    for i in v {
+        match i {
+            Func(_) => {},
+            List(l) => out.append(&mut list_nums(l)),
+            _ => out.push(i.as_f64().unwrap_or(f64::NAN))
+        }
+    }
+
+    out
+}
+
+pub fn list_op(f: &'static (impl Fn(&[f64]) -> Item + Sync + Send)) -> Op {
+    Box::new(move |stack: &mut Stack| {
+        if let Some(l) = stack.pop_as_list() {
+            stack.push(f(&list_nums(&l)));
+        }
+    })
+}
+
+// takes a list and a number, i.e. a percentile
+pub fn list_arg_op(f: &'static (impl Fn(&[f64], f64) -> Item + Sync + Send)) -> Op {
+    Box::new(move |stack: &mut Stack| {
+        if stack.len() < 2 {
+            return;
+        }
+
+        let x = if let Some(x) = stack.pop_as_num() {x} else {return};
+
+        match stack.pop_as_list() {
+            Some(l) => stack.push(f(&list_nums(&l), x)),
+            None => stack.push(Num(x))
+        }
+    })
+}
+
+// takes two lists of the same length
+pub fn list_op_2(f: &'static (impl Fn(&[f64], &[f64]) -> Item + Sync + Send)) -> Op {
+    Box::new(move |stack: &mut Stack| {
+        if stack.len() < 2 {
+            return;
+        }
+
+        let l2 = if let Some(l) = stack.pop_as_list() {l} else {return};
+        let l1 = if let Some(l) = stack.pop_as_list() {l} else {
+            stack.push(List(l2));
+            return
+        };
+
+        let (x, y) = (list_nums(&l1), list_nums(&l2));
+
+        if x.len() == y.len() {
+            stack.push(f(&x, &y));
+        } else {
+            stack.push(List(l1));
+            stack.push(List(l2));
+            stack.set_error(
+                format!("lists have different lengths: {} and {}", x.len(), y.len())
+            );
+        }
+    })
+}
+
+// On failure, the arguments are put back and the error is reported
+pub fn result_op_1(f: &'static (impl Fn(&Item) -> Result<Item, String> + Sync + Send)) -> Op {
+    Box::new(move |stack: &mut Stack| {
+        if let Some(x) = stack.pop() {
+            match f(&x) {
+                Ok(out) => stack.push(out),
+                Err(e) => {
+                    stack.push(x);
+                    stack.set_error(e);
+                }
+            }
+        }
+    })
+}
+
+pub fn result_op_2(f: &'static (impl Fn(&Item, &Item) -> Result<Item, String> + Sync + Send)) -> Op {
+    Box::new(move |stack: &mut Stack| {
+        if stack.len() < 2 {
+            return;
+        }
+
+        let y = stack.pop().unwrap();
+        let x = stack.pop().unwrap();
+
+        match f(&x, &y) {
+            Ok(out) => stack.push(out),
+            Err(e) => {
+                stack.push(x);
+                stack.push(y);
+                stack.set_error(e);
+            }
+        }
+    })
+}
+
+// like result_op_1 and result_op_2, but pushing several results
+pub fn results_op_1(f: &'static (impl Fn(&Item) -> Result<Vec<Item>, String> + Sync + Send)) -> Op {
+    Box::new(move |stack: &mut Stack| {
+        if let Some(x) = stack.pop() {
+            match f(&x) {
+                Ok(out) => out.into_iter().for_each(|i| stack.push(i)),
+                Err(e) => {
+                    stack.push(x);
+                    stack.set_error(e);
+                }
+            }
+        }
+    })
+}
+
+pub fn results_op_2(f: &'static (impl Fn(&Item, &Item) -> Result<Vec<Item>, String> + Sync + Send)) -> Op {
+    Box::new(move |stack: &mut Stack| {
+        if stack.len() < 2 {
+            return;
+        }
+
+        let y = stack.pop().unwrap();
+        let x = stack.pop().unwrap();
+
+        match f(&x, &y) {
+            Ok(out) => out.into_iter().for_each(|i| stack.push(i)),
+            Err(e) => {
+                stack.push(x);
+                stack.push(y);
+                stack.set_error(e);
+            }
+        }
+    })
+}
@@ -0,0 +1,175 @@
+use crate::modes::*;
+use crate::modes::ops::stats::to_points;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Method {
+    Linear,
+    Lagrange,
+    Spline,
+}
+
+pub use Method::*;
+
+pub struct Interpolant {
+    xs: Vec<f64>,
+    ys: Vec<f64>,
+    // barycentric weights for lagrange, second derivatives for splines
+    coeffs: Vec<f64>,
+    method: Method,
+}
+
+// sorts the points by x, rejecting repeated or non finite values
+fn sample_points(points: &Item) -> Result<(Vec<f64>, Vec<f64>), String> {
+    let mut points = to_points(points)?;
+
+    if points.iter().any(|(x, y)| !x.is_finite() || !y.is_finite()) {
+        return Err("sample points must be finite".to_string());
+    }
+
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    if points.windows(2).any(|w| w[0].0 == w[1].0) {
+        return Err("sample points must have distinct x values".to_string());
+    }
+
+    Ok(points.into_iter().unzip())
+}
+
+fn lagrange_weights(xs: &[f64]) -> Vec<f64> {
+    xs.iter()
+        .enumerate()
+        .map(|(j, xj)| {
+            1. / xs.iter()
+                .enumerate()
+                .filter(|(k, _)| *k != j)
+                .map(|(_, xk)| xj - xk)
+                .product::<f64>()
+        })
+        .collect()
+}
+
+// second derivatives of the natural cubic spline, by the thomas algorithm
+fn spline_moments(xs: &[f64], ys: &[f64]) -> Vec<f64> {
+    let n = xs.len();
+    let mut out = vec![0.; n];
+
+    if n < 3 {
+        return out;
+    }
+
+    let h: Vec<f64> = xs.windows(2).map(|w| w[1] - w[0]).collect();
+    let mut diag = vec![0.; n];
+    let mut rhs = vec![0.; n];
+
+    for i in 1..n - 1 {
+        diag[i] = 2. * (h[i - 1] + h[i]);
+        rhs[i] = 6. * ((ys[i + 1] - ys[i]) / h[i] - (ys[i] - ys[i - 1]) / h[i - 1]);
+
+        if i > 1 {
+            let m = h[i - 1] / diag[i - 1];
+            diag[i] -= m * h[i - 1];
+            rhs[i] -= m * rhs[i - 1];
+        }
+    }
+
+    for i in (1..n - 1).rev() {
+        out[i] = (rhs[i] - h[i] * out[i + 1]) / diag[i];
+    }
+
+    out
+}
+
+impl Interpolant {
+    pub fn new(method: Method, points: &Item) -> Result<Self, String> {
+        let (xs, ys) = sample_points(points)?;
+
+        let min_points = if let Lagrange = method {1} else {2};
+
+        if xs.len() < min_points {
+            return Err(format!("expected at least {} sample points", min_points));
+        }
+
+        let coeffs = match method {
+            Linear => Vec::new(),
+            Lagrange => lagrange_weights(&xs),
+            Spline => spline_moments(&xs, &ys),
+        };
+
+        Ok(Interpolant {xs, ys, coeffs, method})
+    }
+
+    // the interval containing x, using the end intervals to extrapolate
+    fn interval(&self, x: f64) -> usize {
+        let i = self.xs.partition_point(|xi| *xi <= x);
+        i.clamp(1, self.xs.len() - 1) - 1
+    }
+
+    pub fn eval(&self, x: f64) -> f64 {
+        let (xs, ys) = (&self.xs, &self.ys);
+
+        match self.method {
+            Linear => {
+                let i = self.interval(x);
+                let t = (x - xs[i]) / (xs[i + 1] - xs[i]);
+
+                ys[i] + t * (ys[i + 1] - ys[i])
+            }
+            Lagrange => {
+                let mut num = 0.;
+                let mut den = 0.;
+
+                for ((xi, yi), wi) in xs.iter().zip(ys).zip(&self.coeffs) {
+                    if x == *xi {
+                        return *yi;
+                    }
+
+                    let t = wi / (x - xi);
+                    num += t * yi;
+                    den += t;
+                }
+
+                num / den
+            }
+            Spline => {
+                let m = &self.coeffs;
+                let i = self.interval(x);
+                let h = xs[i + 1] - xs[i];
+                let a = xs[i + 1] - x;
+                let b = x - xs[i];
+
+                (m[i] * a.powi(3) + m[i + 1] * b.powi(3)) / (6. * h)
+                    + (ys[i] / h - m[i] * h / 6.) * a
+                    + (ys[i + 1] / h - m[i + 1] * h / 6.) * b
+            }
+        }
+    }
+
+    // evaluates at a number, or at every number in a (nested) list
+    pub fn eval_item(&self, x: &Item) -> Result<Item, String> {
+        match x {
+            List(l) => Ok(List(
+                l.iter().map(|x| self.eval_item(x)).collect::<Result<_, _>>()?
+            )),
+            _ => x.as_f64()
+                .map(|x| Num(self.eval(x)))
+                .ok_or_else(|| "expected a number or list of numbers to interpolate at".to_string())
+        }
+    }
+}
+
+pub fn interp_item(method: Method, points: &Item, x: &Item) -> Result<Item, String> {
+    Interpolant::new(method, points)?.eval_item(x)
+}
+
+// a func which interpolates the points with a natural cubic spline
+pub fn interp_func_item(points: &Item) -> Result<Item, String> {
+    let (xs, ys) = sample_points(points)?;
+    Interpolant::new(Spline, points)?;
+
+    let points: Vec<String> = xs.iter()
+        .zip(ys)
+        .map(|(x, y)| format!("[{} {}]", x, y))
+        .collect();
+
+    Ok(Func(format!("[{}] swap interp_spline", points.join(" "))))
+}
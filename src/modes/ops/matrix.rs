@@ -0,0 +1,518 @@
+use num_rational::BigRational;
+use num_traits::{Zero, One, Signed};
+
+use crate::modes::*;
+
+// numbers matrices can be made of, so integer matrices stay exact
+pub trait Field: Clone + PartialEq {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn add(&self, other: &Self) -> Self;
+    fn sub(&self, other: &Self) -> Self;
+    fn mul(&self, other: &Self) -> Self;
+    fn div(&self, other: &Self) -> Self;
+    // used to choose pivots and detect zeros
+    fn size(&self) -> f64;
+    fn is_exact() -> bool;
+    fn to_item(&self) -> Item;
+}
+
+impl Field for f64 {
+    fn zero() -> Self {0.}
+    fn one() -> Self {1.}
+    fn add(&self, other: &Self) -> Self {self + other}
+    fn sub(&self, other: &Self) -> Self {self - other}
+    fn mul(&self, other: &Self) -> Self {self * other}
+    fn div(&self, other: &Self) -> Self {self / other}
+    fn size(&self) -> f64 {self.abs()}
+    fn is_exact() -> bool {false}
+    fn to_item(&self) -> Item {Num(*self)}
+}
+
+impl Field for BigRational {
+    fn zero() -> Self {Zero::zero()}
+    fn one() -> Self {One::one()}
+    fn add(&self, other: &Self) -> Self {self + other}
+    fn sub(&self, other: &Self) -> Self {self - other}
+    fn mul(&self, other: &Self) -> Self {self * other}
+    fn div(&self, other: &Self) -> Self {self / other}
+    fn size(&self) -> f64 {crate::stack::rational_to_f64(&self.abs())}
+    fn is_exact() -> bool {true}
+    fn to_item(&self) -> Item {Rational(self.clone())}
+}
+
+pub type Matrix<T> = Vec<Vec<T>>;
+
+// Matrices are either exact, if all entries are rationals or words, or
+// floating point
+pub enum AnyMatrix {
+    Exact(Matrix<BigRational>),
+    Approx(Matrix<f64>),
+}
+
+pub use AnyMatrix::*;
+
+// a matrix, or a vector which is treated as a column
+pub fn to_matrix(x: &Item) -> Result<(AnyMatrix, bool), String> {
+    let rows = if let List(l) = x {l} else {
+        return Err("expected a matrix".to_string());
+    };
+
+    let (rows, is_vector) =
+        if rows.iter().all(|r| matches!(r, List(_))) && !rows.is_empty() {
+            (rows.iter().map(|r| if let List(r) = r {r.clone()} else {unreachable!()}).collect(), false)
+        } else {
+            (rows.iter().map(|r| vec![r.clone()]).collect::<Vec<_>>(), true)
+        };
+
+    let cols = rows.first().map_or(0, |r| r.len());
+
+    if cols == 0 {
+        return Err("matrix is empty".to_string());
+    }
+    if rows.iter().any(|r| r.len() != cols) {
+        return Err("matrix rows have different lengths".to_string());
+    }
+
+    let exact: Option<Matrix<BigRational>> = rows.iter()
+        .map(|r| r.iter().map(|i| i.as_rational()).collect())
+        .collect();
+
+    if let Some(m) = exact {
+        return Ok((Exact(m), is_vector));
+    }
+
+    let float: Option<Matrix<f64>> = rows.iter()
+        .map(|r| r.iter().map(|i| i.as_f64()).collect())
+        .collect();
+
+    match float {
+        Some(m) => Ok((Approx(m), is_vector)),
+        None => Err("matrix entries must be real numbers".to_string())
+    }
+}
+
+pub fn to_float(m: AnyMatrix) -> Matrix<f64> {
+    match m {
+        Exact(m) => m.iter()
+            .map(|r| r.iter().map(crate::stack::rational_to_f64).collect())
+            .collect(),
+        Approx(m) => m
+    }
+}
+
+// makes both matrices exact or both floating point
+pub fn unify(a: AnyMatrix, b: AnyMatrix) -> (AnyMatrix, AnyMatrix) {
+    match (a, b) {
+        (Exact(a), Exact(b)) => (Exact(a), Exact(b)),
+        (a, b) => (Approx(to_float(a)), Approx(to_float(b)))
+    }
+}
+
+pub fn from_matrix<T: Field>(m: &Matrix<T>) -> Item {
+    List(m.iter().map(|r| List(r.iter().map(T::to_item).collect())).collect())
+}
+
+fn shape<T>(m: &Matrix<T>) -> (usize, usize) {
+    (m.len(), m.first().map_or(0, |r| r.len()))
+}
+
+fn shape_error<T>(op: &str, a: &Matrix<T>, b: &Matrix<T>) -> String {
+    let (a, b) = (shape(a), shape(b));
+    format!("cannot {} {}x{} and {}x{} matrices", op, a.0, a.1, b.0, b.1)
+}
+
+fn require_square<T>(m: &Matrix<T>) -> Result<usize, String> {
+    let (r, c) = shape(m);
+
+    if r == c {
+        Ok(r)
+    } else {
+        Err(format!("expected a square matrix, got {}x{}", r, c))
+    }
+}
+
+fn tolerance<T: Field>(m: &Matrix<T>) -> f64 {
+    if T::is_exact() {
+        return 0.;
+    }
+
+    let (r, c) = shape(m);
+    let max = m.iter().flatten().map(T::size).fold(0., f64::max);
+
+    max * r.max(c) as f64 * f64::EPSILON
+}
+
+pub fn identity<T: Field>(n: usize) -> Matrix<T> {
+    (0..n)
+        .map(|i| (0..n).map(|j| if i == j {T::one()} else {T::zero()}).collect())
+        .collect()
+}
+
+pub fn transpose<T: Field>(m: &Matrix<T>) -> Matrix<T> {
+    let (r, c) = shape(m);
+
+    (0..c).map(|j| (0..r).map(|i| m[i][j].clone()).collect()).collect()
+}
+
+pub fn matmul<T: Field>(a: &Matrix<T>, b: &Matrix<T>) -> Result<Matrix<T>, String> {
+    let (n, m) = shape(a);
+    let (m2, p) = shape(b);
+
+    if m != m2 {
+        return Err(shape_error("multiply", a, b));
+    }
+
+    Ok((0..n).map(|i| {
+        (0..p).map(|j| {
+            (0..m).fold(T::zero(), |acc, k| acc.add(&a[i][k].mul(&b[k][j])))
+        }).collect()
+    }).collect())
+}
+
+pub fn trace<T: Field>(m: &Matrix<T>) -> Result<T, String> {
+    let n = require_square(m)?;
+
+    Ok((0..n).fold(T::zero(), |acc, i| acc.add(&m[i][i])))
+}
+
+// l, u, the permutation as row indices, and the number of row swaps
+type Lu<T> = (Matrix<T>, Matrix<T>, Vec<usize>, usize);
+
+// LU decomposition with partial pivoting, so that PA = LU
+pub fn lu<T: Field>(a: &Matrix<T>) -> Result<Lu<T>, String> {
+    let n = require_square(a)?;
+    let tol = tolerance(a);
+    let mut u = a.clone();
+    let mut l: Matrix<T> = vec![vec![T::zero(); n]; n];
+    let mut perm: Vec<usize> = (0..n).collect();
+    let mut swaps = 0;
+
+    for k in 0..n {
+        let pivot = (k..n)
+            .max_by(|i, j| u[*i][k].size().total_cmp(&u[*j][k].size()))
+            .unwrap();
+
+        if pivot != k {
+            u.swap(pivot, k);
+            l.swap(pivot, k);
+            perm.swap(pivot, k);
+            swaps += 1;
+        }
+
+        if u[k][k].size() <= tol {
+            continue;
+        }
+
+        let pivot_row = u[k].clone();
+
+        for i in k + 1..n {
+            let factor = u[i][k].div(&pivot_row[k]);
+
+            for (x, p) in u[i].iter_mut().zip(&pivot_row).skip(k) {
+                *x = x.sub(&factor.mul(p));
+            }
+
+            l[i][k] = factor;
+        }
+    }
+
+    for (i, row) in l.iter_mut().enumerate() {
+        row[i] = T::one();
+    }
+
+    Ok((l, u, perm, swaps))
+}
+
+pub fn det<T: Field>(a: &Matrix<T>) -> Result<T, String> {
+    let (_, u, _, swaps) = lu(a)?;
+    let mut out = (0..u.len()).fold(T::one(), |acc, i| acc.mul(&u[i][i]));
+
+    if swaps % 2 == 1 {
+        out = T::zero().sub(&out);
+    }
+
+    Ok(out)
+}
+
+// reduces a matrix to row echelon form, returning the pivot columns
+fn row_reduce<T: Field>(m: &mut Matrix<T>, tol: f64) -> Vec<usize> {
+    let (rows, cols) = shape(m);
+    let mut pivots = Vec::new();
+    let mut r = 0;
+
+    for c in 0..cols {
+        if r == rows {
+            break;
+        }
+
+        let pivot = (r..rows)
+            .max_by(|i, j| m[*i][c].size().total_cmp(&m[*j][c].size()))
+            .unwrap();
+
+        if m[pivot][c].size() <= tol {
+            continue;
+        }
+
+        m.swap(pivot, r);
+
+        let p = m[r][c].clone();
+
+        for x in m[r].iter_mut() {
+            *x = x.div(&p);
+        }
+
+        let pivot_row = m[r].clone();
+
+        for (i, row) in m.iter_mut().enumerate() {
+            if i != r {
+                let factor = row[c].clone();
+
+                for (x, p) in row.iter_mut().zip(&pivot_row) {
+                    *x = x.sub(&factor.mul(p));
+                }
+            }
+        }
+
+        pivots.push(c);
+        r += 1;
+    }
+
+    pivots
+}
+
+pub fn rank<T: Field>(m: &Matrix<T>) -> usize {
+    let tol = tolerance(m);
+    row_reduce(&mut m.clone(), tol).len()
+}
+
+// solves ax = b for x, where b may have several columns
+pub fn solve<T: Field>(a: &Matrix<T>, b: &Matrix<T>) -> Result<Matrix<T>, String> {
+    let n = require_square(a)?;
+
+    if b.len() != n {
+        return Err(shape_error("solve with", a, b));
+    }
+
+    let tol = tolerance(a);
+    let mut aug: Matrix<T> = a.iter()
+        .zip(b)
+        .map(|(r1, r2)| r1.iter().chain(r2).cloned().collect())
+        .collect();
+
+    let pivots = row_reduce(&mut aug, tol);
+
+    if pivots.len() < n || pivots[n - 1] >= n {
+        return Err("matrix is singular".to_string());
+    }
+
+    Ok(aug.into_iter().map(|r| r[n..].to_vec()).collect())
+}
+
+pub fn inverse<T: Field>(a: &Matrix<T>) -> Result<Matrix<T>, String> {
+    let n = require_square(a)?;
+    solve(a, &identity(n))
+}
+
+// QR decomposition with householder reflections, so that A = QR
+pub fn qr(a: &Matrix<f64>) -> (Matrix<f64>, Matrix<f64>) {
+    let (m, n) = shape(a);
+    let mut r = a.clone();
+    let mut q = identity::<f64>(m);
+
+    for k in 0..n.min(m.saturating_sub(1)) {
+        let norm = (k..m).map(|i| r[i][k] * r[i][k]).sum::<f64>().sqrt();
+
+        if norm == 0. {
+            continue;
+        }
+
+        let alpha = if r[k][k] > 0. {-norm} else {norm};
+        let mut v: Vec<f64> = (0..m).map(|i| if i < k {0.} else {r[i][k]}).collect();
+        v[k] -= alpha;
+
+        let vnorm = v.iter().map(|x| x * x).sum::<f64>();
+
+        if vnorm == 0. {
+            continue;
+        }
+
+        // r = (I - 2vv^T/v^Tv) r, q = q (I - 2vv^T/v^Tv)
+        let dots: Vec<f64> = (0..n)
+            .map(|j| (k..m).map(|i| v[i] * r[i][j]).sum())
+            .collect();
+
+        for i in k..m {
+            for (x, dot) in r[i].iter_mut().zip(&dots) {
+                *x -= 2. * v[i] * dot / vnorm;
+            }
+        }
+
+        for row in q.iter_mut() {
+            let dot = (k..m).map(|i| row[i] * v[i]).sum::<f64>();
+
+            for i in k..m {
+                row[i] -= 2. * v[i] * dot / vnorm;
+            }
+        }
+    }
+
+    for (i, row) in r.iter_mut().enumerate() {
+        for x in row.iter_mut().take(i.min(n)) {
+            *x = 0.;
+        }
+    }
+
+    (q, r)
+}
+
+// the x minimizing |ax - b|, for a with at least as many rows as columns,
+// using the QR decomposition rather than the less accurate normal equations
+pub fn least_squares(a: &Matrix<f64>, b: &Matrix<f64>) -> Result<Matrix<f64>, String> {
+    let (m, n) = shape(a);
+
+    if b.len() != m || m < n {
+        return Err(shape_error("solve with", a, b));
+    }
+
+    let (q, r) = qr(a);
+    let qtb = matmul(&transpose(&q), b)?;
+    let mut x: Matrix<f64> = vec![vec![0.; shape(b).1]; n];
+
+    // back substitution through the top n rows of r
+    for i in (0..n).rev() {
+        if r[i][i].abs() <= f64::EPSILON * r[0][0].abs() * n as f64 {
+            return Err("matrix does not have full column rank".to_string());
+        }
+
+        let row: Vec<f64> = qtb[i].iter()
+            .enumerate()
+            .map(|(c, y)| (y - (i + 1..n).map(|j| r[i][j] * x[j][c]).sum::<f64>()) / r[i][i])
+            .collect();
+
+        x[i] = row;
+    }
+
+    Ok(x)
+}
+
+fn flatten_row(m: Matrix<Item>) -> Item {
+    List(m.into_iter().flatten().collect())
+}
+
+// runs a function on a matrix, whether it is exact or not
+macro_rules! with_matrix {
+    ($m:expr, |$name:ident| $body:expr) => {
+        match $m {
+            Exact($name) => $body,
+            Approx($name) => $body,
+        }
+    }
+}
+
+pub fn det_item(x: &Item) -> Result<Item, String> {
+    with_matrix!(to_matrix(x)?.0, |m| det(&m).map(|d| d.to_item()))
+}
+
+pub fn trace_item(x: &Item) -> Result<Item, String> {
+    with_matrix!(to_matrix(x)?.0, |m| trace(&m).map(|t| t.to_item()))
+}
+
+pub fn rank_item(x: &Item) -> Result<Item, String> {
+    Ok(Item::exact(with_matrix!(to_matrix(x)?.0, |m| rank(&m)) as i64))
+}
+
+pub fn inverse_item(x: &Item) -> Result<Item, String> {
+    with_matrix!(to_matrix(x)?.0, |m| inverse(&m).map(|m| from_matrix(&m)))
+}
+
+fn to_items<T: Field>(m: Matrix<T>) -> Matrix<Item> {
+    m.iter().map(|r| r.iter().map(T::to_item).collect()).collect()
+}
+
+// Flat lists are treated as row vectors on the left, and column vectors on
+// the right. The result is flattened if either argument was a vector.
+pub fn matmul_item(a: &Item, b: &Item) -> Result<Item, String> {
+    let (ma, va) = to_matrix(a)?;
+    let (mb, vb) = to_matrix(b)?;
+
+    let out =
+        match unify(ma, mb) {
+            (Exact(a), Exact(b)) => {
+                let a = if va {transpose(&a)} else {a};
+                to_items(matmul(&a, &b)?)
+            }
+            (a, b) => {
+                let (a, b) = (to_float(a), to_float(b));
+                let a = if va {transpose(&a)} else {a};
+                to_items(matmul(&a, &b)?)
+            }
+        };
+
+    match (va, vb) {
+        (true, true) => Ok(out[0][0].clone()),
+        (false, false) => Ok(List(out.into_iter().map(List).collect())),
+        _ => Ok(flatten_row(out))
+    }
+}
+
+pub fn solve_item(a: &Item, b: &Item) -> Result<Item, String> {
+    let (ma, _) = to_matrix(a)?;
+    let (mb, vb) = to_matrix(b)?;
+
+    let out =
+        match unify(ma, mb) {
+            (Exact(a), Exact(b)) => to_items(solve(&a, &b)?),
+            (a, b) => to_items(solve(&to_float(a), &to_float(b))?)
+        };
+
+    if vb {
+        Ok(flatten_row(out))
+    } else {
+        Ok(List(out.into_iter().map(List).collect()))
+    }
+}
+
+fn permutation_matrix<T: Field>(perm: &[usize]) -> Matrix<T> {
+    perm.iter()
+        .map(|p| (0..perm.len()).map(|j| if j == *p {T::one()} else {T::zero()}).collect())
+        .collect()
+}
+
+// returns [l u p] where pa = lu
+pub fn lu_item(x: &Item) -> Result<Item, String> {
+    with_matrix!(to_matrix(x)?.0, |m| {
+        let (l, u, perm, _) = lu(&m)?;
+
+        Ok(List(vec![
+            from_matrix(&l),
+            from_matrix(&u),
+            from_matrix(&permutation_matrix::<BigRational>(&perm)),
+        ]))
+    })
+}
+
+// returns [q r] where a = qr
+pub fn qr_item(x: &Item) -> Result<Item, String> {
+    let (q, r) = qr(&to_float(to_matrix(x)?.0));
+
+    Ok(List(vec![from_matrix(&q), from_matrix(&r)]))
+}
+
+fn as_size(x: &Item) -> Result<usize, String> {
+    match x.as_rational() {
+        Some(r) if r.is_integer() && !r.is_negative() && r <= BigRational::from_integer(1000.into()) =>
+            Ok(num_traits::ToPrimitive::to_usize(&r.to_integer()).unwrap()),
+        _ => Err("expected a matrix size from 0 to 1000".to_string())
+    }
+}
+
+pub fn identity_item(n: &Item) -> Result<Item, String> {
+    Ok(from_matrix(&identity::<BigRational>(as_size(n)?)))
+}
+
+pub fn zero_item(rows: &Item, cols: &Item) -> Result<Item, String> {
+    let (rows, cols) = (as_size(rows)?, as_size(cols)?);
+
+    Ok(from_matrix(&vec![vec![<BigRational as Zero>::zero(); cols]; rows]))
+}
@@ -0,0 +1,220 @@
+use crate::modes::*;
+use crate::modes::ops::func_helpers::call_func_nums;
+use crate::modes::ops::autodiff::jacobian;
+
+const MAX_ITERATIONS: usize = 20000;
+
+fn nums_list(x: &[f64]) -> Item {
+    List(x.iter().map(|x| Num(*x)).collect())
+}
+
+// the value of a func taking a list of parameters, treating nan as infinitely
+// bad so that minimizers move away from it
+fn eval_at(ui: &mut Ui, f: &str, x: &[f64]) -> Result<f64, String> {
+    match call_func_nums(ui, f, vec![nums_list(x)])?[..] {
+        [y] if y.is_nan() => Ok(f64::INFINITY),
+        [y] => Ok(y),
+        _ => Err("expected the func to return a single number".to_string())
+    }
+}
+
+fn max_norm(x: impl Iterator<Item = f64>) -> f64 {
+    x.fold(0., |acc, x| acc.max(x.abs()))
+}
+
+// returns the minimum, its value, and the number of iterations taken
+pub fn nelder_mead(ui: &mut Ui, f: &str, x0: &[f64]) -> Result<(Vec<f64>, f64, usize), String> {
+    let n = x0.len();
+    let mut simplex = vec![(x0.to_vec(), eval_at(ui, f, x0)?)];
+
+    for i in 0..n {
+        let mut x = x0.to_vec();
+        x[i] += if x[i] == 0. {0.1} else {0.05 * x[i]};
+
+        let y = eval_at(ui, f, &x)?;
+        simplex.push((x, y));
+    }
+
+    // moves from the centroid c away from or towards x by a factor t
+    let along = |c: &[f64], x: &[f64], t: f64| -> Vec<f64> {
+        c.iter().zip(x).map(|(c, x)| c + t * (x - c)).collect()
+    };
+
+    for iteration in 0..MAX_ITERATIONS {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let (best, f_best) = simplex[0].clone();
+        let f_worst = simplex[n].1;
+
+        let size = simplex[1..].iter()
+            .map(|(x, _)| max_norm(x.iter().zip(&best).map(|(a, b)| a - b)))
+            .fold(0., f64::max);
+
+        let scale = max_norm(best.iter().cloned()).max(1.);
+
+        if size <= 1e-8 * scale && f_worst - f_best <= 1e-12 * f_best.abs().max(1.) {
+            return Ok((best, f_best, iteration));
+        }
+
+        let centroid: Vec<f64> = (0..n)
+            .map(|i| simplex[..n].iter().map(|(x, _)| x[i]).sum::<f64>() / n as f64)
+            .collect();
+
+        let worst = simplex[n].0.clone();
+        let reflected = along(&centroid, &worst, -1.);
+        let f_reflected = eval_at(ui, f, &reflected)?;
+
+        if f_reflected < f_best {
+            let expanded = along(&centroid, &worst, -2.);
+            let f_expanded = eval_at(ui, f, &expanded)?;
+
+            simplex[n] = if f_expanded < f_reflected {
+                (expanded, f_expanded)
+            } else {
+                (reflected, f_reflected)
+            };
+            continue;
+        } else if f_reflected < simplex[n - 1].1 {
+            simplex[n] = (reflected, f_reflected);
+            continue;
+        }
+
+        let (contracted, limit) = if f_reflected < f_worst {
+            (along(&centroid, &reflected, 0.5), f_reflected)
+        } else {
+            (along(&centroid, &worst, 0.5), f_worst)
+        };
+        let f_contracted = eval_at(ui, f, &contracted)?;
+
+        if f_contracted < limit {
+            simplex[n] = (contracted, f_contracted);
+        } else {
+            // shrink every point towards the best one
+            for point in simplex[1..].iter_mut() {
+                let x = along(&best, &point.0, 0.5);
+                let y = eval_at(ui, f, &x)?;
+                *point = (x, y);
+            }
+        }
+    }
+
+    Err("the nelder-mead method did not converge".to_string())
+}
+
+fn project(x: &[f64], bounds: &[(f64, f64)]) -> Vec<f64> {
+    x.iter().zip(bounds).map(|(x, (lo, hi))| x.max(*lo).min(*hi)).collect()
+}
+
+fn dot(x: &[f64], y: &[f64]) -> f64 {
+    x.iter().zip(y).map(|(x, y)| x * y).sum()
+}
+
+fn gradient(ui: &mut Ui, f: &str, x: &[f64]) -> Result<Vec<f64>, String> {
+    match jacobian(ui, f, x)?.pop() {
+        Some(g) if g.len() == x.len() => Ok(g),
+        _ => Err("expected the func to return a single number".to_string())
+    }
+}
+
+// projected gradient descent with barzilai-borwein steps and a backtracking
+// line search, keeping each parameter within its bounds
+pub fn bounded_min(ui: &mut Ui, f: &str, x0: &[f64], bounds: &[(f64, f64)])
+    -> Result<(Vec<f64>, f64, usize), String>
+{
+    let mut x = project(x0, bounds);
+    let mut fx = eval_at(ui, f, &x)?;
+    let mut g = gradient(ui, f, &x)?;
+    let mut step = 1. / max_norm(g.iter().cloned()).max(1.);
+
+    if !fx.is_finite() {
+        return Err("the func is not finite at the starting point".to_string());
+    }
+
+    for iteration in 0..MAX_ITERATIONS {
+        let moved: Vec<f64> = x.iter().zip(&g).map(|(x, g)| x - g).collect();
+        let projected = max_norm(project(&moved, bounds).iter().zip(&x).map(|(a, b)| a - b));
+
+        if projected <= 1e-10 * (1. + fx.abs()) {
+            return Ok((x, fx, iteration));
+        }
+
+        let (x_new, f_new) = loop {
+            let moved: Vec<f64> = x.iter().zip(&g).map(|(x, g)| x - step * g).collect();
+            let x_new = project(&moved, bounds);
+            let change: Vec<f64> = x_new.iter().zip(&x).map(|(a, b)| a - b).collect();
+            let f_new = eval_at(ui, f, &x_new)?;
+
+            if f_new <= fx + 1e-4 * dot(&g, &change) {
+                break (x_new, f_new);
+            }
+
+            step /= 2.;
+
+            // no step decreases the func, so x is as good as it can get
+            if step < 1e-20 {
+                return Ok((x, fx, iteration));
+            }
+        };
+
+        let g_new = gradient(ui, f, &x_new)?;
+        let s: Vec<f64> = x_new.iter().zip(&x).map(|(a, b)| a - b).collect();
+        let y: Vec<f64> = g_new.iter().zip(&g).map(|(a, b)| a - b).collect();
+        let sy = dot(&s, &y);
+
+        step = if sy > 0. {(dot(&s, &s) / sy).clamp(1e-10, 1e10)} else {step * 2.};
+
+        x = x_new;
+        fx = f_new;
+        g = g_new;
+    }
+
+    Err("the gradient method did not converge".to_string())
+}
+
+fn nums(x: &Item) -> Option<Vec<f64>> {
+    match x {
+        List(l) => l.iter().map(Item::as_f64).collect(),
+        _ => None
+    }
+}
+
+fn min_result((x, y, iterations): (Vec<f64>, f64, usize)) -> Vec<Item> {
+    vec![List(vec![nums_list(&x), Num(y), Num(iterations as f64)])]
+}
+
+pub fn nelder_mead_item(ui: &mut Ui, args: &[Item]) -> Result<Vec<Item>, String> {
+    match args {
+        [Func(f), x] => {
+            let x = nums(x)
+                .filter(|x| !x.is_empty())
+                .ok_or_else(|| "expected a list of numbers as the guess".to_string())?;
+
+            nelder_mead(ui, f, &x).map(min_result)
+        }
+        _ => Err("expected a func and a guess".to_string())
+    }
+}
+
+pub fn bounded_min_item(ui: &mut Ui, args: &[Item]) -> Result<Vec<Item>, String> {
+    let bounds_err = || "expected a list of [low high] bounds for each parameter".to_string();
+
+    match args {
+        [Func(f), x, List(bounds)] => {
+            let x = nums(x)
+                .filter(|x| !x.is_empty())
+                .ok_or_else(|| "expected a list of numbers as the guess".to_string())?;
+
+            let bounds: Vec<(f64, f64)> = bounds.iter()
+                .map(|b| match nums(b)?[..] {
+                    [lo, hi] if lo <= hi => Some((lo, hi)),
+                    _ => None
+                })
+                .collect::<Option<_>>()
+                .filter(|b: &Vec<_>| b.len() == x.len())
+                .ok_or_else(bounds_err)?;
+
+            bounded_min(ui, f, &x, &bounds).map(min_result)
+        }
+        _ => Err("expected a func, a guess and a list of bounds".to_string())
+    }
+}
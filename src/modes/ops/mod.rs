@@ -3,9 +3,24 @@ use crate::modes::*;
 
 mod table; 
 mod helpers;
+mod stats;
+mod special;
+pub mod number_theory;
+mod matrix;
+mod eigen;
+mod poly;
+mod interp;
+mod fft;
+mod vector;
 
 mod func_table; 
 mod func_helpers;
+mod ode;
+mod quadrature;
+mod autodiff;
+mod roots;
+mod minimize;
+pub mod finance;
 
 use crate::modes::ops::helpers::Op;
 use crate::modes::ops::table::gen_ops;
@@ -0,0 +1,576 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Zero, Signed, ToPrimitive};
+
+use crate::modes::*;
+
+type IntFn1 = fn(&BigInt) -> Option<BigInt>;
+type IntFn2 = fn(&BigInt, &BigInt) -> Option<BigInt>;
+
+fn as_int(x: &BigRational) -> Option<BigInt> {
+    if x.is_integer() {
+        Some(x.to_integer())
+    } else {
+        None
+    }
+}
+
+// floats are only used if they are integers which are exactly representable
+fn float_as_int(x: f64) -> Option<BigInt> {
+    if x == x.trunc() && x.abs() <= 9007199254740992. {
+        Some(BigInt::from(x as i64))
+    } else {
+        None
+    }
+}
+
+fn int_to_float(x: Option<BigInt>) -> f64 {
+    x.and_then(|x| x.to_f64()).unwrap_or(f64::NAN)
+}
+
+pub fn exact_1(f: IntFn1, x: &BigRational) -> Option<BigRational> {
+    f(&as_int(x)?).map(BigRational::from_integer)
+}
+
+pub fn exact_2(f: IntFn2, x: &BigRational, y: &BigRational) -> Option<BigRational> {
+    f(&as_int(x)?, &as_int(y)?).map(BigRational::from_integer)
+}
+
+pub fn float_1(f: IntFn1, x: f64) -> f64 {
+    int_to_float(float_as_int(x).and_then(|x| f(&x)))
+}
+
+pub fn float_2(f: IntFn2, x: f64, y: f64) -> f64 {
+    match (float_as_int(x), float_as_int(y)) {
+        (Some(x), Some(y)) => int_to_float(f(&x, &y)),
+        _ => f64::NAN
+    }
+}
+
+pub fn gcd(x: &BigInt, y: &BigInt) -> Option<BigInt> {
+    Some(x.gcd(y))
+}
+
+pub fn lcm(x: &BigInt, y: &BigInt) -> Option<BigInt> {
+    Some(x.lcm(y))
+}
+
+pub fn floor_div(x: &BigInt, y: &BigInt) -> Option<BigInt> {
+    if y.is_zero() {None} else {Some(x.div_floor(y))}
+}
+
+pub fn floor_mod(x: &BigInt, y: &BigInt) -> Option<BigInt> {
+    if y.is_zero() {None} else {Some(x.mod_floor(y))}
+}
+
+pub fn mod_inverse(x: &BigInt, m: &BigInt) -> Option<BigInt> {
+    if *m <= BigInt::one() {
+        return None;
+    }
+
+    let e = x.mod_floor(m).extended_gcd(m);
+
+    if e.gcd.is_one() {
+        Some(e.x.mod_floor(m))
+    } else {
+        None
+    }
+}
+
+pub fn mod_pow(x: &BigInt, e: &BigInt, m: &BigInt) -> Option<BigInt> {
+    if m.is_zero() {
+        return None;
+    }
+
+    if e.is_negative() {
+        mod_inverse(x, &m.abs()).map(|inv| inv.modpow(&-e, &m.abs()))
+    } else {
+        Some(x.mod_floor(&m.abs()).modpow(e, &m.abs()))
+    }
+}
+
+// Miller-Rabin with the first twelve primes as bases, which is exact for
+// numbers below 3.3e24, and very unlikely to be wrong above that
+pub fn is_prime(n: &BigInt) -> bool {
+    const BASES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if *n < BigInt::from(2) {
+        return false;
+    }
+
+    for p in BASES.iter() {
+        if n % p == BigInt::zero() {
+            return *n == BigInt::from(*p);
+        }
+    }
+
+    let one = BigInt::one();
+    let n1 = n - &one;
+    let s = n1.trailing_zeros().unwrap_or(0);
+    let d = &n1 >> s;
+
+    'outer: for a in BASES.iter() {
+        let mut x = BigInt::from(*a).modpow(&d, n);
+
+        if x == one || x == n1 {
+            continue;
+        }
+
+        for _ in 1..s {
+            x = &x * &x % n;
+
+            if x == n1 {
+                continue 'outer;
+            }
+        }
+
+        return false;
+    }
+
+    true
+}
+
+pub fn is_prime_int(n: &BigInt) -> Option<BigInt> {
+    Some(if is_prime(n) {BigInt::one()} else {BigInt::zero()})
+}
+
+pub fn next_prime(n: &BigInt) -> Option<BigInt> {
+    let mut n: BigInt = n + 1;
+
+    if n <= BigInt::from(2) {
+        return Some(BigInt::from(2));
+    }
+
+    if n.is_even() {
+        n += 1;
+    }
+
+    while !is_prime(&n) {
+        n += 2;
+    }
+
+    Some(n)
+}
+
+pub fn prev_prime(n: &BigInt) -> Option<BigInt> {
+    let mut n: BigInt = n - 1;
+
+    if n < BigInt::from(2) {
+        return None;
+    }
+    if n == BigInt::from(2) {
+        return Some(n);
+    }
+
+    if n.is_even() {
+        n -= 1;
+    }
+
+    while !is_prime(&n) {
+        n -= 2;
+    }
+
+    Some(n)
+}
+
+// pollard's rho only finds factors up to about the square of this many steps
+// in reasonable time, so larger factors give up rather than hang
+const RHO_STEPS: u32 = 1 << 16;
+const RHO_CONSTANTS: u32 = 2;
+// steps between each gcd
+const RHO_BATCH: u32 = 128;
+
+// finds a nontrivial factor of an odd composite number with brent's variant
+// of pollard's rho, or none if it takes too long
+fn pollard_rho(n: &BigInt) -> Option<BigInt> {
+    for c in 1..=RHO_CONSTANTS {
+        let f = |x: &BigInt| (x * x + c) % n;
+        let mut y = BigInt::from(2);
+        let mut x = y.clone();
+        let mut saved = y.clone();
+        let mut q = BigInt::one();
+        let mut d = BigInt::one();
+        let mut r = 1;
+
+        while d.is_one() && r <= RHO_STEPS {
+            x = y.clone();
+
+            for _ in 0..r {
+                y = f(&y);
+            }
+
+            let mut k = 0;
+
+            while k < r && d.is_one() {
+                saved = y.clone();
+
+                for _ in 0..RHO_BATCH.min(r - k) {
+                    y = f(&y);
+                    q = q * (&x - &y).abs() % n;
+                }
+
+                d = q.gcd(n);
+                k += RHO_BATCH;
+            }
+
+            r *= 2;
+        }
+
+        // the batch multiplied in every factor, so retrace it one at a time
+        if d == *n {
+            loop {
+                saved = f(&saved);
+                d = (&x - &saved).abs().gcd(n);
+
+                if !d.is_one() {
+                    break;
+                }
+            }
+        }
+
+        if !d.is_one() && d != *n {
+            return Some(d);
+        }
+    }
+
+    None
+}
+
+// prime factors in increasing order, with repeats
+pub fn factor(n: &BigInt) -> Result<Vec<BigInt>, String> {
+    let mut n = n.abs();
+    let mut out = Vec::new();
+
+    if n.is_zero() {
+        return Ok(out);
+    }
+
+    for p in [2u32, 3, 5, 7, 11, 13].iter() {
+        while (&n % p).is_zero() {
+            out.push(BigInt::from(*p));
+            n /= *p;
+        }
+    }
+
+    let mut stack = vec![n];
+
+    while let Some(n) = stack.pop() {
+        if n.is_one() {
+            continue;
+        }
+
+        if is_prime(&n) {
+            out.push(n);
+        } else {
+            let d = pollard_rho(&n).ok_or_else(|| "number too large to factor".to_string())?;
+            stack.push(&n / &d);
+            stack.push(d);
+        }
+    }
+
+    out.sort();
+    Ok(out)
+}
+
+pub fn totient(n: &BigInt) -> Result<BigInt, String> {
+    let mut factors = factor(n)?;
+    factors.dedup();
+
+    Ok(factors.iter().fold(n.clone(), |acc, p| acc / p * (p - 1)))
+}
+
+// the value of an integer item, and whether it is exact
+fn item_as_int(x: &Item) -> Option<(BigInt, bool)> {
+    match x {
+        Rational(_) | Integer(_) => x.as_rational().and_then(|x| as_int(&x)).map(|n| (n, true)),
+        Num(n) => float_as_int(*n).map(|n| (n, false)),
+        _ => None
+    }
+}
+
+// the prime factorization of an integer as a list, or nan, applied to each
+// number in a list
+pub fn factor_item(x: &Item) -> Result<Item, String> {
+    match x {
+        List(l) => Ok(List(l.iter().map(factor_item).collect::<Result<_, _>>()?)),
+        _ => match item_as_int(x) {
+            Some((n, exact)) => Ok(List(
+                factor(&n)?.into_iter()
+                    .map(|p| if exact {Rational(p.into())} else {Num(int_to_float(Some(p)))})
+                    .collect()
+            )),
+            None => Ok(Num(f64::NAN))
+        }
+    }
+}
+
+// euler's totient of a positive integer, or nan, applied to each number in a
+// list
+pub fn totient_item(x: &Item) -> Result<Item, String> {
+    match x {
+        List(l) => Ok(List(l.iter().map(totient_item).collect::<Result<_, _>>()?)),
+        _ => match item_as_int(x) {
+            Some((n, _)) if n.is_positive() => {
+                let t = totient(&n)?;
+
+                Ok(match x {
+                    Integer(w) => Integer(w.with_int(&t)),
+                    Num(_) => Num(int_to_float(Some(t))),
+                    _ => Rational(t.into())
+                })
+            }
+            _ => Ok(Num(f64::NAN))
+        }
+    }
+}
+
+// applies modular exponentiation to the base, exponent, and modulus
+pub fn mod_pow_items(x: Item, e: Item, m: Item) -> Item {
+    let s = [x, e, m];
+
+    let exact: Option<Vec<BigInt>> =
+        s.iter().map(|i| i.as_rational().and_then(|r| as_int(&r))).collect();
+
+    if let (Some(v), false) = (exact, s.iter().any(|i| matches!(i, Num(_)))) {
+        return match (mod_pow(&v[0], &v[1], &v[2]), &s[0]) {
+            (Some(out), Integer(w)) => Integer(w.with_int(&out)),
+            (Some(out), _) => Rational(out.into()),
+            (None, _) => Num(f64::NAN)
+        };
+    }
+
+    let float: Option<Vec<BigInt>> =
+        s.iter().map(|i| i.as_f64().and_then(float_as_int)).collect();
+
+    match float {
+        Some(v) => Num(int_to_float(mod_pow(&v[0], &v[1], &v[2]))),
+        None => Num(f64::NAN)
+    }
+}
+
+// the terms of the continued fraction of a fraction, with each term after the
+// first positive
+pub fn continued_fraction(x: &BigRational) -> Vec<BigInt> {
+    let mut x = x.clone();
+    let mut out = Vec::new();
+
+    loop {
+        let a = x.floor();
+        out.push(a.to_integer());
+
+        if x == a {
+            return out;
+        }
+
+        x = (x - a).recip();
+    }
+}
+
+fn from_continued_fraction(terms: &[BigInt]) -> BigRational {
+    let mut terms = terms.iter().rev();
+    let last = BigRational::from_integer(terms.next().cloned().unwrap_or_default());
+
+    terms.fold(last, |acc, a| acc.recip() + BigRational::from_integer(a.clone()))
+}
+
+// the closest fraction to x with a denominator of at most max_den, which is
+// either a convergent of its continued fraction or a semiconvergent between
+// the last two convergents which fit
+pub fn closest_fraction(x: &BigRational, max_den: &BigInt) -> BigRational {
+    let (mut p0, mut q0) = (BigInt::zero(), BigInt::one());
+    let (mut p1, mut q1) = (BigInt::one(), BigInt::zero());
+
+    for a in continued_fraction(x) {
+        let (p2, q2) = (&a * &p1 + &p0, &a * &q1 + &q0);
+
+        if q2 > *max_den {
+            let m = (max_den - &q0) / &q1;
+            let conv = BigRational::new(p1.clone(), q1.clone());
+
+            if m.is_zero() {
+                return conv;
+            }
+
+            let semi = BigRational::new(&m * &p1 + &p0, &m * &q1 + &q0);
+
+            return if (&semi - x).abs() < (&conv - x).abs() {semi} else {conv};
+        }
+
+        p0 = p1;
+        q0 = q1;
+        p1 = p2;
+        q1 = q2;
+    }
+
+    BigRational::new(p1, q1)
+}
+
+// the fraction with the smallest denominator between lo and hi, inclusive,
+// found by following their continued fractions until they differ
+pub fn simplest_between(lo: &BigRational, hi: &BigRational) -> BigRational {
+    if hi.is_negative() {
+        return -simplest_between(&-hi, &-lo);
+    } else if !lo.is_positive() {
+        return BigRational::zero();
+    }
+
+    let (mut lo, mut hi) = (lo.clone(), hi.clone());
+    let mut terms = Vec::new();
+
+    loop {
+        let a = lo.ceil();
+
+        if a <= hi {
+            terms.push(a.to_integer());
+            return from_continued_fraction(&terms);
+        }
+
+        let a = lo.floor();
+        terms.push(a.to_integer());
+
+        let (next_lo, next_hi) = ((&hi - &a).recip(), (&lo - &a).recip());
+        lo = next_lo;
+        hi = next_hi;
+    }
+}
+
+fn exact_num(x: &Item) -> Result<BigRational, String> {
+    match x {
+        Num(n) => BigRational::from_float(*n),
+        Rational(r) => Some(r.clone()),
+        Integer(w) => Some(BigRational::from_integer(w.to_bigint())),
+        _ => None
+    }.ok_or_else(|| "expected a finite number".to_string())
+}
+
+// the simplest fraction within the tolerance of a number, or of each number in
+// a list
+pub fn fraction_item(x: &Item, tol: &Item) -> Result<Item, String> {
+    let tol = tol.as_f64()
+        .filter(|t| *t >= 0.)
+        .and_then(BigRational::from_float)
+        .ok_or_else(|| "expected a tolerance of at least 0".to_string())?;
+
+    fraction_rec(x, &tol)
+}
+
+fn fraction_rec(x: &Item, tol: &BigRational) -> Result<Item, String> {
+    match x {
+        List(l) => Ok(List(l.iter().map(|x| fraction_rec(x, tol)).collect::<Result<_, _>>()?)),
+        _ => {
+            let x = exact_num(x)?;
+            Ok(Rational(simplest_between(&(&x - tol), &(&x + tol))))
+        }
+    }
+}
+
+// the terms of a continued fraction, as a list. floats are treated as the
+// simplest fraction which rounds to them, so that 0.1 gives [0 10].
+pub fn continued_fraction_item(x: &Item) -> Result<Item, String> {
+    let r = match x {
+        Num(n) => {
+            let half_ulp = BigRational::from_float(n.abs() * f64::EPSILON / 2.).unwrap_or_default();
+            let x = exact_num(x)?;
+
+            simplest_between(&(&x - &half_ulp), &(&x + &half_ulp))
+        }
+        _ => exact_num(x)?
+    };
+
+    Ok(List(continued_fraction(&r).into_iter().map(|a| Rational(a.into())).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_prime_naive(n: u64) -> bool {
+        n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn is_prime_matches_trial_division() {
+        for n in 0..2000u64 {
+            assert_eq!(is_prime(&BigInt::from(n)), is_prime_naive(n), "{}", n);
+        }
+
+        assert!(!is_prime(&BigInt::from(-7)));
+        // strong pseudoprimes to several small bases
+        assert!(!is_prime(&BigInt::from(3_215_031_751u64)));
+        assert!(!is_prime(&BigInt::from(3_825_123_056_546_413_051u64)));
+        assert!(is_prime(&BigInt::from(1_000_000_007u64)));
+        assert!(is_prime(&((BigInt::one() << 61) - 1)));
+    }
+
+    #[test]
+    fn factor_multiplies_back_to_its_input() {
+        let cases: [(u64, &[u64]); 6] = [
+            (1, &[]),
+            (12, &[2, 2, 3]),
+            (97, &[97]),
+            (1001, &[7, 11, 13]),
+            (600_851_475_143, &[71, 839, 1471, 6857]),
+            (1_000_000_016_000_000_063, &[1_000_000_007, 1_000_000_009]),
+        ];
+
+        for (n, expected) in cases.iter() {
+            let expected: Vec<BigInt> = expected.iter().map(|p| BigInt::from(*p)).collect();
+
+            assert_eq!(factor(&BigInt::from(*n)), Ok(expected.clone()));
+            assert_eq!(factor(&-BigInt::from(*n)), Ok(expected));
+        }
+
+        assert_eq!(factor(&BigInt::zero()), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn factor_gives_up_on_large_semiprimes() {
+        let p = next_prime(&BigInt::from(10u64.pow(19))).unwrap();
+        let q = next_prime(&p).unwrap();
+        assert_eq!(factor(&(&p * &q)), Err("number too large to factor".to_string()));
+        assert_eq!(factor(&(&p * 12)), Ok(vec![2.into(), 2.into(), 3.into(), p]));
+    }
+
+    fn ratio(n: i64, d: i64) -> BigRational {
+        BigRational::new(BigInt::from(n), BigInt::from(d))
+    }
+
+    #[test]
+    fn continued_fractions_round_trip() {
+        let terms = |t: &[i64]| t.iter().map(|a| BigInt::from(*a)).collect::<Vec<_>>();
+
+        assert_eq!(continued_fraction(&ratio(415, 93)), terms(&[4, 2, 6, 7]));
+        assert_eq!(continued_fraction(&ratio(-7, 3)), terms(&[-3, 1, 2]));
+        assert_eq!(continued_fraction(&ratio(5, 1)), terms(&[5]));
+
+        for (n, d) in [(415, 93), (-7, 3), (355, 113), (1, 7)].iter() {
+            let x = ratio(*n, *d);
+            assert_eq!(from_continued_fraction(&continued_fraction(&x)), x);
+        }
+    }
+
+    #[test]
+    fn closest_fraction_with_a_bounded_denominator() {
+        let den = |d: i64| BigInt::from(d);
+
+        assert_eq!(closest_fraction(&ratio(313, 1000), &den(16)), ratio(5, 16));
+        assert_eq!(closest_fraction(&ratio(31, 100), &den(16)), ratio(4, 13));
+        assert_eq!(closest_fraction(&ratio(3, 10), &den(16)), ratio(3, 10));
+        assert_eq!(closest_fraction(&ratio(-31, 100), &den(16)), ratio(-4, 13));
+        // a semiconvergent, since the convergents are 3, 22/7 and 333/106
+        assert_eq!(closest_fraction(&ratio(314159, 100000), &den(20)), ratio(22, 7));
+        assert_eq!(closest_fraction(&ratio(314159, 100000), &den(100)), ratio(311, 99));
+        assert_eq!(closest_fraction(&ratio(7, 2), &den(1)), ratio(3, 1));
+        assert_eq!(closest_fraction(&ratio(37, 10), &den(1)), ratio(4, 1));
+    }
+
+    #[test]
+    fn simplest_between_finds_the_smallest_denominator() {
+        assert_eq!(simplest_between(&ratio(3, 10), &ratio(4, 10)), ratio(1, 3));
+        assert_eq!(simplest_between(&ratio(-4, 10), &ratio(-3, 10)), ratio(-1, 3));
+        assert_eq!(simplest_between(&ratio(-1, 2), &ratio(1, 2)), ratio(0, 1));
+        assert_eq!(simplest_between(&ratio(314, 100), &ratio(315, 100)), ratio(22, 7));
+        assert_eq!(simplest_between(&ratio(2, 1), &ratio(2, 1)), ratio(2, 1));
+    }
+}
@@ -0,0 +1,200 @@
+use crate::modes::*;
+use crate::modes::ops::func_helpers::{call_func, results_func_op};
+
+#[derive(Clone, Copy, Debug)]
+pub enum Method {
+    Euler,
+    Rk4,
+    Rk45,
+}
+
+pub use Method::*;
+
+// dormand-prince coefficients, with the fifth order weights as the last row
+const DP_C: [f64; 6] = [1. / 5., 3. / 10., 4. / 5., 8. / 9., 1., 1.];
+
+const DP_A: [&[f64]; 6] = [
+    &[1. / 5.],
+    &[3. / 40., 9. / 40.],
+    &[44. / 45., -56. / 15., 32. / 9.],
+    &[19372. / 6561., -25360. / 2187., 64448. / 6561., -212. / 729.],
+    &[9017. / 3168., -355. / 33., 46732. / 5247., 49. / 176., -5103. / 18656.],
+    &[35. / 384., 0., 500. / 1113., 125. / 192., -2187. / 6784., 11. / 84.],
+];
+
+// difference between the fifth and fourth order weights
+const DP_E: [f64; 7] = [
+    35. / 384. - 5179. / 57600.,
+    0.,
+    500. / 1113. - 7571. / 16695.,
+    125. / 192. - 393. / 640.,
+    -2187. / 6784. + 92097. / 339200.,
+    11. / 84. - 187. / 2100.,
+    -1. / 40.,
+];
+
+const TOLERANCE: f64 = 1e-10;
+const MAX_STEPS: usize = 100000;
+
+// y is a single number, or a list of numbers for a system of equations
+struct System<'a> {
+    f: &'a str,
+    is_list: bool,
+    len: usize,
+}
+
+impl System<'_> {
+    fn to_item(&self, y: &[f64]) -> Item {
+        if self.is_list {
+            List(y.iter().map(|y| Num(*y)).collect())
+        } else {
+            Num(y[0])
+        }
+    }
+
+    fn slope(&self, ui: &mut Ui, x: f64, y: &[f64]) -> Result<Vec<f64>, String> {
+        let out = match call_func(ui, self.f, vec![Num(x), self.to_item(y)]) {
+            Some(List(l)) if self.is_list => l.iter().map(Item::as_f64).collect(),
+            Some(n) if !self.is_list => n.as_f64().map(|n| vec![n]),
+            _ => None
+        };
+
+        out.filter(|k| k.len() == self.len)
+            .ok_or_else(|| "expected the func to return a slope for each y value".to_string())
+    }
+}
+
+// y + h * (a_1 k_1 + a_2 k_2 + ...)
+fn combine(y: &[f64], h: f64, ks: &[Vec<f64>], a: &[f64]) -> Vec<f64> {
+    (0..y.len())
+        .map(|i| y[i] + h * ks.iter().zip(a).map(|(k, a)| a * k[i]).sum::<f64>())
+        .collect()
+}
+
+// y + h * k
+fn shift(y: &[f64], h: f64, k: &[f64]) -> Vec<f64> {
+    y.iter().zip(k).map(|(y, k)| y + h * k).collect()
+}
+
+fn rk4_step(sys: &System, ui: &mut Ui, x: f64, y: &[f64], k1: Vec<f64>, h: f64)
+    -> Result<Vec<f64>, String>
+{
+    let k2 = sys.slope(ui, x + h / 2., &shift(y, h / 2., &k1))?;
+    let k3 = sys.slope(ui, x + h / 2., &shift(y, h / 2., &k2))?;
+    let k4 = sys.slope(ui, x + h, &shift(y, h, &k3))?;
+
+    Ok(combine(y, h / 6., &[k1, k2, k3, k4], &[1., 2., 2., 1.]))
+}
+
+// integrates from x to x + h, taking as many dormand-prince steps as needed
+fn rk45_interval(sys: &System, ui: &mut Ui, x: f64, y: &[f64], k1: Vec<f64>, h: f64)
+    -> Result<Vec<f64>, String>
+{
+    let end = x + h;
+    let mut x = x;
+    let mut y = y.to_vec();
+    let mut h = h;
+    let mut ks = vec![k1];
+
+    for _ in 0..MAX_STEPS {
+        if (end - x).abs() <= f64::EPSILON * end.abs().max(1.) {
+            return Ok(y);
+        }
+
+        if (x + h - end) * h > 0. {
+            h = end - x;
+        }
+
+        ks.truncate(1);
+
+        for (c, a) in DP_C.iter().zip(DP_A.iter()) {
+            let k = sys.slope(ui, x + c * h, &combine(&y, h, &ks, a))?;
+            ks.push(k);
+        }
+
+        let y_new = combine(&y, h, &ks, DP_A[5]);
+        let err = combine(&vec![0.; y.len()], h, &ks, &DP_E)
+            .iter()
+            .zip(y.iter().zip(&y_new))
+            .map(|(e, (a, b))| e.abs() / (TOLERANCE * (1. + a.abs().max(b.abs()))))
+            .fold(0., f64::max);
+
+        if err.is_nan() {
+            return Err("the solution is not finite".to_string());
+        }
+
+        if err <= 1. {
+            x += h;
+            y = y_new;
+            // the last stage is the slope at the new point
+            ks.swap_remove(0);
+        }
+
+        h *= (0.9 * err.powf(-0.2)).clamp(0.2, 5.);
+
+        if h.abs() <= f64::EPSILON * x.abs().max(1.) {
+            return Err("the step size became too small".to_string());
+        }
+    }
+
+    Err("too many steps were needed".to_string())
+}
+
+fn solve(ui: &mut Ui, method: Method, log: bool, args: &[Item])
+    -> Result<Vec<Item>, String>
+{
+    let (f, start, endx, steps) = match args {
+        [Func(f), List(start), endx, steps] => (f, start, endx, steps),
+        _ => return Err("expected a func, [x y], the final x, and a number of steps".to_string())
+    };
+
+    let steps = steps.as_f64()
+        .filter(|n| *n >= 1.)
+        .ok_or_else(|| "expected a positive number of steps".to_string())? as usize;
+    let endx = endx.as_f64().ok_or_else(|| "expected a number for the final x".to_string())?;
+
+    let err = || "expected a starting point [x y], with y a number or list of numbers".to_string();
+
+    let (x, y, is_list) = match start.as_slice() {
+        [x, List(y)] => (x.as_f64(), y.iter().map(Item::as_f64).collect(), true),
+        [x, y] => (x.as_f64(), y.as_f64().map(|y| vec![y]), false),
+        _ => return Err(err())
+    };
+
+    let mut x = x.ok_or_else(err)?;
+    let mut y: Vec<f64> = y.ok_or_else(err)?;
+
+    let sys = System {f, is_list, len: y.len()};
+    let delta = (endx - x) / steps as f64;
+    let mut rows = Vec::new();
+
+    for i in 0..steps {
+        let k1 = sys.slope(ui, x, &y)?;
+
+        if log {
+            rows.push(List(vec![Num(x), sys.to_item(&y), sys.to_item(&k1)]));
+        }
+
+        y = match method {
+            Euler => shift(&y, delta, &k1),
+            Rk4 => rk4_step(&sys, ui, x, &y, k1, delta)?,
+            Rk45 => rk45_interval(&sys, ui, x, &y, k1, delta)?,
+        };
+        x = if i + 1 == steps {endx} else {x + delta};
+    }
+
+    let mut out = Vec::new();
+
+    if log {
+        out.push(List(rows));
+    }
+    out.push(List(vec![Num(x), sys.to_item(&y)]));
+
+    Ok(out)
+}
+
+// takes a func, [x y], the final x and a number of steps, where the func
+// takes x and y and returns the slope of y
+pub fn ode_solve(method: Method, log: bool, ui: &mut Ui) {
+    results_func_op(ui, 4, |ui, args| solve(ui, method, log, args))
+}
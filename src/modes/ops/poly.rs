@@ -0,0 +1,119 @@
+use num_complex::Complex64;
+
+use crate::modes::*;
+use crate::modes::ops::eigen::eigenvalues;
+
+fn eval(poly: &[Complex64], x: Complex64) -> (Complex64, Complex64) {
+    let mut p = Complex64::new(0., 0.);
+    let mut dp = Complex64::new(0., 0.);
+
+    for c in poly {
+        dp = dp * x + p;
+        p = p * x + c;
+    }
+
+    (p, dp)
+}
+
+// Durand-Kerner iteration, used for polynomials with complex coefficients
+fn durand_kerner(poly: &[Complex64]) -> Vec<Complex64> {
+    let n = poly.len() - 1;
+    let seed = Complex64::new(0.4, 0.9);
+    let mut roots: Vec<Complex64> = (0..n).map(|i| seed.powu(i as u32)).collect();
+
+    for _ in 0..1000 {
+        let mut change: f64 = 0.;
+
+        for i in 0..n {
+            let denom: Complex64 = (0..n)
+                .filter(|j| *j != i)
+                .map(|j| roots[i] - roots[j])
+                .product();
+
+            let delta = eval(poly, roots[i]).0 / denom;
+
+            roots[i] -= delta;
+            change = change.max(delta.norm());
+        }
+
+        if change < 1e-15 {
+            break;
+        }
+    }
+
+    roots
+}
+
+// all roots of a polynomial with coefficients in decreasing order of degree
+pub fn poly_roots(poly: &[Complex64]) -> Result<Vec<Complex64>, String> {
+    let start = poly.iter().position(|c| c.norm() != 0.).unwrap_or(poly.len());
+    let poly = &poly[start..];
+
+    if poly.is_empty() {
+        return Err("the zero polynomial has infinitely many roots".to_string());
+    }
+
+    let monic: Vec<Complex64> = poly.iter().map(|c| c / poly[0]).collect();
+    let n = monic.len() - 1;
+
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    let real = monic.iter().all(|c| c.im == 0.);
+
+    let mut roots =
+        if real {
+            // eigenvalues of the companion matrix
+            let mut companion = vec![vec![0.; n]; n];
+
+            for (j, c) in monic[1..].iter().enumerate() {
+                companion[0][j] = -c.re;
+            }
+            for i in 1..n {
+                companion[i][i - 1] = 1.;
+            }
+
+            eigenvalues(&companion)?
+        } else {
+            durand_kerner(&monic)
+        };
+
+    // polish with newton's method
+    for root in roots.iter_mut() {
+        for _ in 0..3 {
+            let (p, dp) = eval(&monic, *root);
+
+            if dp.norm() == 0. {
+                break;
+            }
+
+            let next = *root - p / dp;
+
+            if eval(&monic, next).0.norm() < p.norm() {
+                *root = next;
+            } else {
+                break;
+            }
+        }
+
+        if real && root.im.abs() <= 1e-14 * root.norm() {
+            root.im = 0.;
+        }
+    }
+
+    roots.sort_by(|x, y| y.re.total_cmp(&x.re).then(y.im.total_cmp(&x.im)));
+
+    Ok(roots)
+}
+
+pub fn poly_roots_item(x: &Item) -> Result<Item, String> {
+    let l = if let List(l) = x {l} else {
+        return Err("expected a polynomial".to_string());
+    };
+
+    let poly: Option<Vec<Complex64>> = l.iter().map(Item::as_complex).collect();
+    let poly = poly.ok_or_else(|| "polynomial coefficients must be numbers".to_string())?;
+
+    Ok(List(poly_roots(&poly)?.into_iter().map(complex_item).collect()))
+}
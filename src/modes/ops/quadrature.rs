@@ -0,0 +1,221 @@
+use crate::modes::*;
+use crate::modes::ops::func_helpers::call_func;
+
+// 15 point kronrod nodes on [-1, 1], with every other node from the 7 point
+// gauss rule, and the center last
+const KRONROD_X: [f64; 8] = [
+    0.9914553711208126, 0.9491079123427585, 0.8648644233597691, 0.7415311855993945,
+    0.5860872354676911, 0.4058451513773972, 0.20778495500789848, 0.,
+];
+
+const KRONROD_W: [f64; 8] = [
+    0.022935322010529224, 0.06309209262997856, 0.10479001032225019, 0.14065325971552592,
+    0.1690047266392679, 0.19035057806478542, 0.20443294007529889, 0.20948214108472782,
+];
+
+const GAUSS_W: [f64; 4] = [
+    0.1294849661688697, 0.27970539148927664, 0.3818300505051189, 0.4179591836734694,
+];
+
+const MAX_INTERVALS: usize = 500;
+
+// infinite bounds are mapped onto a finite interval of t
+#[derive(Clone, Copy, Debug)]
+enum Transform {
+    Finite,
+    Upper(f64),
+    Lower(f64),
+    Both,
+}
+
+use Transform::*;
+
+impl Transform {
+    // x and dx/dt for a given t
+    fn map(self, t: f64) -> (f64, f64) {
+        match self {
+            Finite => (t, 1.),
+            Upper(a) => (a + t / (1. - t), 1. / (1. - t).powi(2)),
+            Lower(b) => (b - (1. - t) / t, 1. / t.powi(2)),
+            Both => (t / (1. - t * t), (1. + t * t) / (1. - t * t).powi(2)),
+        }
+    }
+}
+
+// runs the func on a list of all the xs at once if it can, and on each x
+// individually otherwise
+fn eval_func(ui: &mut Ui, f: &str, xs: &[f64]) -> Result<Vec<f64>, String> {
+    let error = ui.get_stack().get_error().cloned();
+
+    if let Some(List(l)) = call_func(ui, f, vec![List(xs.iter().map(|x| Num(*x)).collect())]) {
+        if l.len() == xs.len() {
+            if let Some(ys) = l.iter().map(Item::as_f64).collect() {
+                return Ok(ys);
+            }
+        }
+    }
+
+    match error {
+        Some(e) => ui.get_stack().set_error(e),
+        None => ui.get_stack().clear_error()
+    }
+
+    xs.iter()
+        .map(|x| {
+            call_func(ui, f, vec![Num(*x)])
+                .and_then(|y| y.as_f64())
+                .ok_or_else(|| "expected the func to return a number".to_string())
+        })
+        .collect()
+}
+
+// the kronrod estimate of the integral over [lo, hi] of t, with its
+// difference from the gauss estimate as the error
+fn gauss_kronrod(ui: &mut Ui, f: &str, transform: Transform, lo: f64, hi: f64)
+    -> Result<(f64, f64), String>
+{
+    let center = (lo + hi) / 2.;
+    let half = (hi - lo) / 2.;
+
+    let mut ts = Vec::new();
+
+    for x in &KRONROD_X[..7] {
+        ts.push(center - half * x);
+        ts.push(center + half * x);
+    }
+    ts.push(center);
+
+    let (xs, dxs): (Vec<f64>, Vec<f64>) = ts.iter().map(|t| transform.map(*t)).unzip();
+    let ys: Vec<f64> = eval_func(ui, f, &xs)?
+        .iter()
+        .zip(dxs)
+        .map(|(y, dx)| y * dx)
+        .collect();
+
+    if let Some(i) = ys.iter().position(|y| !y.is_finite()) {
+        return Err(format!("the func is not finite at {}", xs[i]));
+    }
+
+    let mut kronrod = KRONROD_W[7] * ys[14];
+    let mut gauss = GAUSS_W[3] * ys[14];
+
+    for i in 0..7 {
+        let pair = ys[2 * i] + ys[2 * i + 1];
+
+        kronrod += KRONROD_W[i] * pair;
+
+        if i % 2 == 1 {
+            gauss += GAUSS_W[i / 2] * pair;
+        }
+    }
+
+    Ok((kronrod * half, (kronrod - gauss).abs() * half))
+}
+
+// adaptive quadrature, splitting the interval with the largest error until
+// the total error is within the tolerance
+pub fn integrate(ui: &mut Ui, f: &str, low: f64, high: f64, tolerance: f64)
+    -> Result<(f64, f64), String>
+{
+    if low.is_nan() || high.is_nan() {
+        return Err("the bounds must be numbers".to_string());
+    }
+
+    if low == high {
+        return Ok((0., 0.));
+    } else if low > high {
+        return integrate(ui, f, high, low, tolerance).map(|(v, e)| (-v, e));
+    }
+
+    let (transform, lo, hi) = match (low.is_finite(), high.is_finite()) {
+        (true, true) => (Finite, low, high),
+        (true, false) => (Upper(low), 0., 1.),
+        (false, true) => (Lower(high), 0., 1.),
+        (false, false) => (Both, -1., 1.),
+    };
+
+    let (value, error) = gauss_kronrod(ui, f, transform, lo, hi)?;
+    let mut intervals = vec![(lo, hi, value, error)];
+
+    loop {
+        let value: f64 = intervals.iter().map(|i| i.2).sum();
+        let error: f64 = intervals.iter().map(|i| i.3).sum();
+
+        if error <= tolerance.max(1e-14 * value.abs()) || intervals.len() >= MAX_INTERVALS {
+            return Ok((value, error));
+        }
+
+        let worst = (0..intervals.len())
+            .max_by(|a, b| intervals[*a].3.total_cmp(&intervals[*b].3))
+            .unwrap();
+
+        let (lo, hi, ..) = intervals[worst];
+        let mid = (lo + hi) / 2.;
+
+        if mid <= lo || mid >= hi {
+            return Ok((value, error));
+        }
+
+        let (v1, e1) = gauss_kronrod(ui, f, transform, lo, mid)?;
+        let (v2, e2) = gauss_kronrod(ui, f, transform, mid, hi)?;
+
+        intervals[worst] = (lo, mid, v1, e1);
+        intervals.push((mid, hi, v2, e2));
+    }
+}
+
+fn integral_args(args: &[Item]) -> Result<(&str, f64, f64), String> {
+    match args {
+        [Func(f), low, high, ..] => match (low.as_f64(), high.as_f64()) {
+            (Some(low), Some(high)) => Ok((f, low, high)),
+            _ => Err("expected numbers for the bounds".to_string())
+        },
+        _ => Err("expected a func and two bounds".to_string())
+    }
+}
+
+// takes a func and two bounds, and pushes the integral
+pub fn area(ui: &mut Ui, args: &[Item]) -> Result<Vec<Item>, String> {
+    let (f, low, high) = integral_args(args)?;
+
+    Ok(vec![Num(integrate(ui, f, low, high, 1e-10)?.0)])
+}
+
+// takes a func, two bounds and a tolerance, and pushes the integral and an
+// estimate of its error
+pub fn integral(ui: &mut Ui, args: &[Item]) -> Result<Vec<Item>, String> {
+    let (f, low, high) = integral_args(args)?;
+    let tolerance = args[3].as_f64()
+        .filter(|t| *t >= 0.)
+        .ok_or_else(|| "expected a tolerance of at least 0".to_string())?;
+
+    let (value, error) = integrate(ui, f, low, high, tolerance)?;
+
+    Ok(vec![List(vec![Num(value), Num(error)])])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn check(f: &str, low: f64, high: f64, expected: f64) {
+        let (value, _) = integrate(&mut Ui::build(), f, low, high, 1e-10).unwrap();
+        assert!((value - expected).abs() < 1e-9, "{} from {} to {}: {} != {}", f, low, high, value, expected);
+    }
+
+    #[test]
+    fn integrate_finite_bounds() {
+        check("square", 0., 3., 9.);
+        check("sin", 0., PI, 2.);
+        check("square", 3., 0., -9.);
+    }
+
+    #[test]
+    fn integrate_infinite_bounds() {
+        check("negate pow", 0., f64::INFINITY, 1.);
+        check("pow", f64::NEG_INFINITY, 0., 1.);
+        check("square negate pow", f64::NEG_INFINITY, f64::INFINITY, PI.sqrt());
+        check("square 1 + invert", f64::INFINITY, f64::NEG_INFINITY, -PI);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::modes::*;
+use crate::modes::ops::func_helpers::call_func_nums;
+use crate::modes::ops::autodiff::{nth_derivative, jacobian, step_size};
+use crate::modes::ops::matrix;
+
+const MAX_ITERATIONS: usize = 200;
+const TOLERANCE: f64 = 1e-14;
+
+fn eval_at(ui: &mut Ui, f: &str, x: f64) -> Result<f64, String> {
+    match call_func_nums(ui, f, vec![Num(x)])?[..] {
+        [y] if y.is_nan() => Err(format!("the func is not a number at {}", x)),
+        [y] => Ok(y),
+        _ => Err("expected the func to return a single number".to_string())
+    }
+}
+
+fn converged(step: f64, x: f64) -> bool {
+    step.abs() <= TOLERANCE * x.abs().max(1.)
+}
+
+pub fn newton(ui: &mut Ui, f: &str, x: f64) -> Result<f64, String> {
+    let mut x = x;
+
+    for _ in 0..MAX_ITERATIONS {
+        let y = eval_at(ui, f, x)?;
+
+        if y == 0. {
+            return Ok(x);
+        }
+
+        let slope = nth_derivative(ui, f, x, 1)?;
+
+        if slope == 0. || !slope.is_finite() {
+            return Err(format!("newton's method failed, with no usable slope at {}", x));
+        }
+
+        let step = y / slope;
+        x -= step;
+
+        if !x.is_finite() {
+            return Err("newton's method diverged".to_string());
+        } else if converged(step, x) {
+            return Ok(x);
+        }
+    }
+
+    Err("newton's method did not converge".to_string())
+}
+
+pub fn secant(ui: &mut Ui, f: &str, x0: f64, x1: f64) -> Result<f64, String> {
+    let mut x0 = x0;
+    let mut x1 = if x0 == x1 {x0 + step_size(x0)} else {x1};
+    let mut y0 = eval_at(ui, f, x0)?;
+
+    for _ in 0..MAX_ITERATIONS {
+        let y1 = eval_at(ui, f, x1)?;
+
+        if y1 == 0. {
+            return Ok(x1);
+        } else if y1 == y0 {
+            return Err(format!("the secant method failed, with a flat secant at {}", x1));
+        }
+
+        let step = y1 * (x1 - x0) / (y1 - y0);
+
+        x0 = x1;
+        y0 = y1;
+        x1 -= step;
+
+        if !x1.is_finite() {
+            return Err("the secant method diverged".to_string());
+        } else if converged(step, x1) {
+            return Ok(x1);
+        }
+    }
+
+    Err("the secant method did not converge".to_string())
+}
+
+fn same_sign(x: f64, y: f64) -> bool {
+    (x > 0. && y > 0.) || (x < 0. && y < 0.)
+}
+
+// brent's method, widening the interval between the guesses until the func
+// changes sign within it
+pub fn brent(ui: &mut Ui, f: &str, a: f64, b: f64) -> Result<f64, String> {
+    brent_by(|x| eval_at(ui, f, x), a, b)
+}
+
+// brent's method for a closure, for finding zeros of functions not written as
+// funcs
+pub fn brent_by(mut f: impl FnMut(f64) -> Result<f64, String>, a: f64, b: f64)
+    -> Result<f64, String>
+{
+    let mut a = a;
+    let mut b = if a == b {a + step_size(a)} else {b};
+    let mut fa = f(a)?;
+    let mut fb = f(b)?;
+
+    for i in 0.. {
+        if !same_sign(fa, fb) {
+            break;
+        } else if i == 50 {
+            return Err("brent's method failed, finding no sign change near the guesses".to_string());
+        }
+
+        if fa.abs() < fb.abs() {
+            a += 1.6 * (a - b);
+            fa = f(a)?;
+        } else {
+            b += 1.6 * (b - a);
+            fb = f(b)?;
+        }
+    }
+
+    let (mut c, mut fc) = (b, fb);
+    let mut d = b - a;
+    let mut e = d;
+
+    for _ in 0..MAX_ITERATIONS {
+        if same_sign(fb, fc) {
+            c = a;
+            fc = fa;
+            d = b - a;
+            e = d;
+        }
+
+        if fc.abs() < fb.abs() {
+            a = b;
+            b = c;
+            c = a;
+            fa = fb;
+            fb = fc;
+            fc = fa;
+        }
+
+        let tol = 2. * f64::EPSILON * b.abs() + 1e-300;
+        let mid = (c - b) / 2.;
+
+        if mid.abs() <= tol || fb == 0. {
+            return Ok(b);
+        }
+
+        if e.abs() >= tol && fa.abs() > fb.abs() {
+            // inverse quadratic interpolation, or the secant method if
+            // only two points are known
+            let s = fb / fa;
+
+            let (p, q) = if a == c {
+                (2. * mid * s, 1. - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+
+                (
+                    s * (2. * mid * q * (q - r) - (b - a) * (r - 1.)),
+                    (q - 1.) * (r - 1.) * (s - 1.)
+                )
+            };
+
+            let (p, q) = if p > 0. {(p, -q)} else {(-p, q)};
+
+            if 2. * p < (3. * mid * q - (tol * q).abs()).min((e * q).abs()) {
+                e = d;
+                d = p / q;
+            } else {
+                d = mid;
+                e = d;
+            }
+        } else {
+            d = mid;
+            e = d;
+        }
+
+        a = b;
+        fa = fb;
+        b += if d.abs() > tol {d} else {tol.copysign(mid)};
+        fb = f(b)?;
+    }
+
+    Err("brent's method did not converge".to_string())
+}
+
+fn nums_list(x: &[f64]) -> Item {
+    List(x.iter().map(|x| Num(*x)).collect())
+}
+
+// newton's method for a func from a list to a list of the same length
+pub fn newton_system(ui: &mut Ui, f: &str, x: Vec<f64>) -> Result<Vec<f64>, String> {
+    let mut x = x;
+
+    for _ in 0..MAX_ITERATIONS {
+        let y = call_func_nums(ui, f, vec![nums_list(&x)])?;
+
+        if y.len() != x.len() {
+            return Err("expected the func to return a list as long as its input".to_string());
+        } else if y.iter().all(|y| *y == 0.) {
+            return Ok(x);
+        }
+
+        let j = jacobian(ui, f, &x)?;
+        let y: Vec<Vec<f64>> = y.into_iter().map(|y| vec![y]).collect();
+
+        let step = matrix::solve(&j, &y)
+            .map_err(|_| "newton's method failed, with a singular jacobian".to_string())?;
+
+        for (x, s) in x.iter_mut().zip(&step) {
+            *x -= s[0];
+        }
+
+        if x.iter().any(|x| !x.is_finite()) {
+            return Err("newton's method diverged".to_string());
+        }
+
+        let scale = x.iter().fold(0., |acc: f64, x| acc.max(x.abs()));
+
+        if step.iter().all(|s| converged(s[0], scale)) {
+            return Ok(x);
+        }
+    }
+
+    Err("newton's method did not converge".to_string())
+}
+
+fn num_arg(x: &Item) -> Result<f64, String> {
+    x.as_f64().ok_or_else(|| "expected a number for the guess".to_string())
+}
+
+fn root_result(ui: &mut Ui, f: &str, x: f64) -> Result<Vec<Item>, String> {
+    let y = eval_at(ui, f, x)?;
+    Ok(vec![List(vec![Num(x), Num(y)])])
+}
+
+pub fn newton_item(ui: &mut Ui, args: &[Item]) -> Result<Vec<Item>, String> {
+    match args {
+        [Func(f), x] => {
+            let x = newton(ui, f, num_arg(x)?)?;
+            root_result(ui, f, x)
+        }
+        _ => Err("expected a func and a guess".to_string())
+    }
+}
+
+pub fn secant_item(ui: &mut Ui, args: &[Item]) -> Result<Vec<Item>, String> {
+    match args {
+        [Func(f), x0, x1] => {
+            let x = secant(ui, f, num_arg(x0)?, num_arg(x1)?)?;
+            root_result(ui, f, x)
+        }
+        _ => Err("expected a func and two guesses".to_string())
+    }
+}
+
+pub fn brent_item(ui: &mut Ui, args: &[Item]) -> Result<Vec<Item>, String> {
+    match args {
+        [Func(f), a, b] => {
+            let x = brent(ui, f, num_arg(a)?, num_arg(b)?)?;
+            root_result(ui, f, x)
+        }
+        _ => Err("expected a func and two guesses".to_string())
+    }
+}
+
+pub fn newton_system_item(ui: &mut Ui, args: &[Item]) -> Result<Vec<Item>, String> {
+    let err = || "expected a func and a list of numbers as the guess".to_string();
+
+    match args {
+        [Func(f), List(x)] => {
+            let x: Vec<f64> = x.iter().map(Item::as_f64).collect::<Option<_>>().ok_or_else(err)?;
+            let x = newton_system(ui, f, x)?;
+            let y = call_func_nums(ui, f, vec![nums_list(&x)])?;
+
+            Ok(vec![List(vec![nums_list(&x), nums_list(&y)])])
+        }
+        _ => Err(err())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval_error(s: &str) -> Option<String> {
+        let mut ui = Ui::build();
+        ui.eval(s.to_string());
+        ui.get_stack().get_error().cloned()
+    }
+
+    #[test]
+    fn brent_by_finds_a_bracketed_root() {
+        let x = brent_by(|x| Ok(x * x - 2.), 0., 2.).unwrap();
+        assert!((x - 2f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn newton_finds_a_root() {
+        let mut ui = Ui::build();
+        ui.eval("( dup * 2 - ) 1 newton".to_string());
+
+        match ui.get_stack().last() {
+            Some(List(l)) => assert!((l[0].as_f64().unwrap() - 2f64.sqrt()).abs() < 1e-12),
+            x => panic!("expected a list, got {:?}", x)
+        }
+    }
+
+    #[test]
+    fn funcs_not_returning_one_number_are_errors() {
+        let err = Some("expected the func to return a single number".to_string());
+
+        assert_eq!(eval_error("( [ ] ) 1 newton"), err);
+        assert_eq!(eval_error("( [ ] ) 0 1 secant"), err);
+        assert_eq!(eval_error("( [ 1 2 ] ) 0 1 brent"), err);
+    }
+}
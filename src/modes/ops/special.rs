@@ -0,0 +1,514 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive};
+
+use std::f64::consts::PI;
+
+const LANCZOS_G: f64 = 7.;
+const LANCZOS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+pub fn gamma(x: f64) -> f64 {
+    if x == x.floor() && x <= 0. {
+        return f64::NAN;
+    }
+
+    if x < 0.5 {
+        // reflection formula
+        return PI / ((PI * x).sin() * gamma(1. - x));
+    }
+
+    if x > 171.7 {
+        return f64::INFINITY;
+    }
+
+    if x == x.floor() {
+        return (2..x as u64).fold(1., |acc, i| acc * i as f64);
+    }
+
+    let x = x - 1.;
+    let t = x + LANCZOS_G + 0.5;
+    let mut sum = LANCZOS[0];
+
+    for (i, c) in LANCZOS.iter().enumerate().skip(1) {
+        sum += c / (x + i as f64);
+    }
+
+    // t^(x + 0.5) alone overflows for x near the largest finite gamma, so it
+    // is split in half with e^-t between the halves
+    let half = t.powf((x + 0.5) / 2.);
+
+    (2. * PI).sqrt() * half * (-t).exp() * half * sum
+}
+
+// natural log of the absolute value of gamma
+pub fn lgamma(x: f64) -> f64 {
+    if x == x.floor() && x <= 0. {
+        return f64::INFINITY;
+    }
+
+    if x < 0.5 {
+        return (PI / (PI * x).sin().abs()).ln() - lgamma(1. - x);
+    }
+
+    let x = x - 1.;
+    let t = x + LANCZOS_G + 0.5;
+    let mut sum = LANCZOS[0];
+
+    for (i, c) in LANCZOS.iter().enumerate().skip(1) {
+        sum += c / (x + i as f64);
+    }
+
+    0.5 * (2. * PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+pub fn beta(a: f64, b: f64) -> f64 {
+    if a.abs() + b.abs() < 170. {
+        return gamma(a) * gamma(b) / gamma(a + b);
+    }
+
+    (lgamma(a) + lgamma(b) - lgamma(a + b)).exp()
+        * gamma(a).signum() * gamma(b).signum() * gamma(a + b).signum()
+}
+
+pub fn factorial(n: f64) -> f64 {
+    gamma(n + 1.)
+}
+
+fn is_count(x: &BigRational, max: u64) -> Option<u64> {
+    if x.is_integer() && !x.is_negative() {
+        x.to_integer().to_u64().filter(|n| *n <= max)
+    } else {
+        None
+    }
+}
+
+fn product(from: u64, to: u64) -> BigInt {
+    (from..=to).fold(BigInt::one(), |acc, i| acc * i)
+}
+
+pub fn exact_factorial(n: &BigRational) -> Option<BigRational> {
+    let n = is_count(n, 10_000)?;
+
+    Some(BigRational::from_integer(product(1, n)))
+}
+
+pub fn ncr(n: f64, k: f64) -> f64 {
+    if k < 0. || k > n {
+        return 0.;
+    }
+
+    let out = (lgamma(n + 1.) - lgamma(k + 1.) - lgamma(n - k + 1.)).exp();
+
+    if n == n.floor() && k == k.floor() {out.round()} else {out}
+}
+
+pub fn npr(n: f64, k: f64) -> f64 {
+    if k < 0. || k > n {
+        return 0.;
+    }
+
+    let out = (lgamma(n + 1.) - lgamma(n - k + 1.)).exp();
+
+    if n == n.floor() && k == k.floor() {out.round()} else {out}
+}
+
+pub fn exact_ncr(n: &BigRational, k: &BigRational) -> Option<BigRational> {
+    let n = is_count(n, 100_000)?;
+    let k = is_count(k, 100_000)?;
+
+    if k > n {
+        return Some(BigRational::from_integer(0.into()));
+    }
+
+    let k = k.min(n - k);
+
+    Some(BigRational::from_integer(product(n - k + 1, n) / product(1, k)))
+}
+
+pub fn exact_npr(n: &BigRational, k: &BigRational) -> Option<BigRational> {
+    let n = is_count(n, 100_000)?;
+    let k = is_count(k, 100_000)?;
+
+    if k > n {
+        return Some(BigRational::from_integer(0.into()));
+    }
+
+    Some(BigRational::from_integer(product(n - k + 1, n)))
+}
+
+const EPS: f64 = 1e-16;
+const MAX_ITER: usize = 1000;
+
+// regularized lower incomplete gamma function P(a, x), and its complement
+fn gamma_p_q(a: f64, x: f64) -> (f64, f64) {
+    if x < 0. || a <= 0. || x.is_nan() || a.is_nan() {
+        return (f64::NAN, f64::NAN);
+    }
+    if x == 0. {
+        return (0., 1.);
+    }
+    if x.is_infinite() {
+        return (1., 0.);
+    }
+
+    let ln_pre = a * x.ln() - x - lgamma(a);
+
+    if x < a + 1. {
+        // series
+        let mut term = 1. / a;
+        let mut sum = term;
+        let mut n = a;
+
+        for _ in 0..MAX_ITER {
+            n += 1.;
+            term *= x / n;
+            sum += term;
+
+            if term.abs() < sum.abs() * EPS {
+                break;
+            }
+        }
+
+        let p = sum * ln_pre.exp();
+        (p, 1. - p)
+    } else {
+        // continued fraction, evaluated with the modified Lentz method
+        let tiny = 1e-300;
+        let mut b = x + 1. - a;
+        let mut c = 1. / tiny;
+        let mut d = 1. / b;
+        let mut h = d;
+
+        for i in 1..MAX_ITER {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.;
+            d = an * d + b;
+            if d.abs() < tiny {d = tiny;}
+            c = b + an / c;
+            if c.abs() < tiny {c = tiny;}
+            d = 1. / d;
+            let del = d * c;
+            h *= del;
+
+            if (del - 1.).abs() < EPS {
+                break;
+            }
+        }
+
+        let q = h * ln_pre.exp();
+        (1. - q, q)
+    }
+}
+
+fn beta_cf(a: f64, b: f64, x: f64) -> f64 {
+    let tiny = 1e-300;
+    let qab = a + b;
+    let qap = a + 1.;
+    let qam = a - 1.;
+    let mut c = 1.;
+    let mut d = 1. - qab * x / qap;
+    if d.abs() < tiny {d = tiny;}
+    d = 1. / d;
+    let mut h = d;
+
+    for m in 1..MAX_ITER {
+        let m = m as f64;
+        let m2 = 2. * m;
+
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1. + aa * d;
+        if d.abs() < tiny {d = tiny;}
+        c = 1. + aa / c;
+        if c.abs() < tiny {c = tiny;}
+        d = 1. / d;
+        h *= d * c;
+
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1. + aa * d;
+        if d.abs() < tiny {d = tiny;}
+        c = 1. + aa / c;
+        if c.abs() < tiny {c = tiny;}
+        d = 1. / d;
+        let del = d * c;
+        h *= del;
+
+        if (del - 1.).abs() < EPS {
+            break;
+        }
+    }
+
+    h
+}
+
+// regularized incomplete beta function I_x(a, b)
+fn beta_inc(a: f64, b: f64, x: f64) -> f64 {
+    if !(0. ..=1.).contains(&x) || a <= 0. || b <= 0. {
+        return f64::NAN;
+    }
+    if x == 0. || x == 1. {
+        return x;
+    }
+
+    let ln_front = lgamma(a + b) - lgamma(a) - lgamma(b)
+        + a * x.ln() + b * (1. - x).ln();
+
+    if x < (a + 1.) / (a + b + 2.) {
+        ln_front.exp() * beta_cf(a, b, x) / a
+    } else {
+        1. - ln_front.exp() * beta_cf(b, a, 1. - x) / b
+    }
+}
+
+pub fn erf(x: f64) -> f64 {
+    if x.is_nan() {
+        return f64::NAN;
+    }
+
+    let p = gamma_p_q(0.5, x * x).0;
+
+    if x < 0. {-p} else {p}
+}
+
+pub fn erfc(x: f64) -> f64 {
+    if x.is_nan() {
+        return f64::NAN;
+    }
+
+    let q = gamma_p_q(0.5, x * x).1;
+
+    if x < 0. {2. - q} else {q}
+}
+
+// Finds x where cdf(x) = p by bisection, for increasing cdfs. If integer is
+// set, finds the smallest integer x with cdf(x) >= p.
+fn inverse_cdf(cdf: impl Fn(f64) -> f64, p: f64, mut lo: f64, mut hi: f64, integer: bool)
+    -> f64
+{
+    if !(0. ..=1.).contains(&p) {
+        return f64::NAN;
+    }
+
+    // expand the bracket until it contains the answer
+    for _ in 0..2000 {
+        if lo.is_finite() && cdf(lo) > p {
+            lo = if lo < 0. {lo * 2.} else {lo - 1. - lo.abs()};
+        } else if hi.is_finite() && cdf(hi) < p {
+            hi = hi * 2. + 1.;
+        } else {
+            break;
+        }
+    }
+
+    for _ in 0..2000 {
+        let mid = lo + (hi - lo) / 2.;
+
+        if integer && hi - lo <= 1. {
+            return if cdf(lo) >= p {lo} else {hi};
+        }
+        if mid <= lo || mid >= hi {
+            break;
+        }
+
+        if cdf(mid) < p {
+            lo = if integer {mid.floor()} else {mid};
+        } else {
+            hi = if integer {mid.ceil()} else {mid};
+        }
+    }
+
+    lo + (hi - lo) / 2.
+}
+
+pub fn normal_pdf(x: f64, mu: f64, sigma: f64) -> f64 {
+    if sigma <= 0. {
+        return f64::NAN;
+    }
+
+    let z = (x - mu) / sigma;
+
+    (-0.5 * z * z).exp() / (sigma * (2. * PI).sqrt())
+}
+
+pub fn normal_cdf(x: f64, mu: f64, sigma: f64) -> f64 {
+    if sigma <= 0. {
+        return f64::NAN;
+    }
+
+    0.5 * erfc(-(x - mu) / (sigma * 2f64.sqrt()))
+}
+
+pub fn normal_inv(p: f64, mu: f64, sigma: f64) -> f64 {
+    match p {
+        _ if sigma <= 0. => f64::NAN,
+        _ if p == 0. => f64::NEG_INFINITY,
+        _ if p == 1. => f64::INFINITY,
+        _ => mu + sigma * inverse_cdf(|z| normal_cdf(z, 0., 1.), p, -1., 1., false)
+    }
+}
+
+pub fn t_pdf(t: f64, df: f64) -> f64 {
+    if df <= 0. {
+        return f64::NAN;
+    }
+
+    (lgamma((df + 1.) / 2.) - lgamma(df / 2.)).exp()
+        / (df * PI).sqrt()
+        * (1. + t * t / df).powf(-(df + 1.) / 2.)
+}
+
+pub fn t_cdf(t: f64, df: f64) -> f64 {
+    if df <= 0. {
+        return f64::NAN;
+    }
+
+    let tail = 0.5 * beta_inc(df / 2., 0.5, df / (df + t * t));
+
+    if t > 0. {1. - tail} else {tail}
+}
+
+pub fn t_inv(p: f64, df: f64) -> f64 {
+    match p {
+        _ if df <= 0. => f64::NAN,
+        _ if p == 0. => f64::NEG_INFINITY,
+        _ if p == 1. => f64::INFINITY,
+        _ => inverse_cdf(|t| t_cdf(t, df), p, -1., 1., false)
+    }
+}
+
+pub fn chi2_pdf(x: f64, k: f64) -> f64 {
+    if k <= 0. {
+        f64::NAN
+    } else if x < 0. {
+        0.
+    } else {
+        ((k / 2. - 1.) * x.ln() - x / 2. - k / 2. * 2f64.ln() - lgamma(k / 2.)).exp()
+    }
+}
+
+pub fn chi2_cdf(x: f64, k: f64) -> f64 {
+    if k <= 0. {
+        f64::NAN
+    } else if x <= 0. {
+        0.
+    } else {
+        gamma_p_q(k / 2., x / 2.).0
+    }
+}
+
+pub fn chi2_inv(p: f64, k: f64) -> f64 {
+    match p {
+        _ if k <= 0. => f64::NAN,
+        _ if p == 1. => f64::INFINITY,
+        _ => inverse_cdf(|x| chi2_cdf(x, k), p, 0., k.max(1.), false)
+    }
+}
+
+fn is_natural(x: f64) -> bool {
+    x >= 0. && x == x.floor()
+}
+
+pub fn binomial_pdf(k: f64, n: f64, p: f64) -> f64 {
+    if !is_natural(n) || !(0. ..=1.).contains(&p) {
+        return f64::NAN;
+    }
+    if !is_natural(k) || k > n {
+        return 0.;
+    }
+
+    if p == 0. || p == 1. {
+        return if (p == 0. && k == 0.) || (p == 1. && k == n) {1.} else {0.};
+    }
+
+    (lgamma(n + 1.) - lgamma(k + 1.) - lgamma(n - k + 1.)
+        + k * p.ln() + (n - k) * (1. - p).ln()).exp()
+}
+
+pub fn binomial_cdf(k: f64, n: f64, p: f64) -> f64 {
+    if !is_natural(n) || !(0. ..=1.).contains(&p) {
+        return f64::NAN;
+    }
+
+    let k = k.floor();
+
+    if k < 0. {
+        0.
+    } else if k >= n || p == 0. {
+        1.
+    } else if p == 1. {
+        0.
+    } else {
+        beta_inc(n - k, k + 1., 1. - p)
+    }
+}
+
+pub fn binomial_inv(q: f64, n: f64, p: f64) -> f64 {
+    if !is_natural(n) || !(0. ..=1.).contains(&p) {
+        return f64::NAN;
+    }
+
+    // the cdf is zero below the support, so for q = 0 the search runs below it
+    inverse_cdf(|k| binomial_cdf(k, n, p), q, 0., n, true).max(0.)
+}
+
+pub fn poisson_pdf(k: f64, lambda: f64) -> f64 {
+    if lambda <= 0. {
+        f64::NAN
+    } else if !is_natural(k) {
+        0.
+    } else {
+        (k * lambda.ln() - lambda - lgamma(k + 1.)).exp()
+    }
+}
+
+pub fn poisson_cdf(k: f64, lambda: f64) -> f64 {
+    if lambda <= 0. {
+        f64::NAN
+    } else if k < 0. {
+        0.
+    } else {
+        gamma_p_q(k.floor() + 1., lambda).1
+    }
+}
+
+pub fn poisson_inv(p: f64, lambda: f64) -> f64 {
+    match p {
+        _ if lambda <= 0. => f64::NAN,
+        _ if p == 1. => f64::INFINITY,
+        _ => inverse_cdf(|k| poisson_cdf(k, lambda), p, 0., lambda.ceil(), true).max(0.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(x: f64, y: f64) -> bool {
+        (x - y).abs() <= 1e-12 * y.abs()
+    }
+
+    #[test]
+    fn gamma_near_its_largest_finite_values() {
+        assert!(close(gamma(5.5), 52.342_777_784_553_52));
+        assert!(close(gamma(170.5), 5.562_092_414_56e305));
+        assert!(close(gamma(171.5), 9.483_367_566_824_8e307));
+        assert!(close(gamma(171.5).ln(), lgamma(171.5)));
+    }
+
+    #[test]
+    fn discrete_inverses_stay_in_their_support() {
+        assert_eq!(binomial_inv(0., 10., 0.5), 0.);
+        assert_eq!(binomial_inv(1., 10., 0.5), 10.);
+        assert_eq!(binomial_inv(0.5, 10., 0.5), 5.);
+        assert_eq!(poisson_inv(0., 3.), 0.);
+    }
+}
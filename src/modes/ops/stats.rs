@@ -0,0 +1,213 @@
+use crate::modes::*;
+use crate::modes::ops::matrix::least_squares;
+
+pub fn mean(x: &[f64]) -> f64 {
+    x.iter().sum::<f64>() / x.len() as f64
+}
+
+fn sorted(x: &[f64]) -> Vec<f64> {
+    let mut out = x.to_vec();
+    out.sort_by(|a, b| a.total_cmp(b));
+    out
+}
+
+// linearly interpolates between the closest ranks, with p from 0 to 100
+pub fn percentile(x: &[f64], p: f64) -> f64 {
+    if x.is_empty() || !(0. ..=100.).contains(&p) {
+        return f64::NAN;
+    }
+
+    let x = sorted(x);
+    let rank = p / 100. * (x.len() - 1) as f64;
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+
+    x[lo] + (x[hi] - x[lo]) * (rank - lo as f64)
+}
+
+pub fn median(x: &[f64]) -> f64 {
+    percentile(x, 50.)
+}
+
+pub fn quartiles(x: &[f64]) -> Item {
+    List(vec![
+        Num(percentile(x, 25.)),
+        Num(percentile(x, 50.)),
+        Num(percentile(x, 75.)),
+    ])
+}
+
+// the most common value, choosing the smallest if there is a tie
+pub fn mode(x: &[f64]) -> f64 {
+    let x = sorted(x);
+    let mut best = (f64::NAN, 0);
+    let mut i = 0;
+
+    while i < x.len() {
+        let count = x[i..].iter().take_while(|y| **y == x[i]).count().max(1);
+
+        if count > best.1 {
+            best = (x[i], count);
+        }
+
+        i += count;
+    }
+
+    best.0
+}
+
+// sum of squared deviations from the mean, divided by n - ddof
+pub fn variance(x: &[f64], ddof: usize) -> f64 {
+    if x.len() <= ddof {
+        return f64::NAN;
+    }
+
+    let m = mean(x);
+
+    x.iter().map(|y| (y - m).powi(2)).sum::<f64>() / (x.len() - ddof) as f64
+}
+
+pub fn min(x: &[f64]) -> f64 {
+    if x.is_empty() {f64::NAN} else {x.iter().cloned().fold(f64::INFINITY, f64::min)}
+}
+
+pub fn max(x: &[f64]) -> f64 {
+    if x.is_empty() {f64::NAN} else {x.iter().cloned().fold(f64::NEG_INFINITY, f64::max)}
+}
+
+pub fn weighted_mean(x: &[f64], w: &[f64]) -> f64 {
+    x.iter().zip(w).map(|(x, w)| x * w).sum::<f64>() / w.iter().sum::<f64>()
+}
+
+// sample covariance
+pub fn covariance(x: &[f64], y: &[f64]) -> f64 {
+    if x.len() < 2 {
+        return f64::NAN;
+    }
+
+    let (mx, my) = (mean(x), mean(y));
+
+    x.iter().zip(y).map(|(x, y)| (x - mx) * (y - my)).sum::<f64>()
+        / (x.len() - 1) as f64
+}
+
+pub fn correlation(x: &[f64], y: &[f64]) -> f64 {
+    covariance(x, y) / (variance(x, 1) * variance(y, 1)).sqrt()
+}
+
+// least squares line through the points, as [slope intercept r^2]
+pub fn linear_regression(x: &[f64], y: &[f64]) -> Item {
+    let slope = covariance(x, y) / variance(x, 1);
+    let intercept = mean(y) - slope * mean(x);
+    let r = correlation(x, y);
+
+    List(vec![Num(slope), Num(intercept), Num(r * r)])
+}
+
+// reads a list of [x y] pairs
+pub fn to_points(x: &Item) -> Result<Vec<(f64, f64)>, String> {
+    let err = || "expected a list of [x y] pairs".to_string();
+    let l = if let List(l) = x {l} else {return Err(err())};
+
+    l.iter()
+        .map(|p| match p {
+            List(p) if p.len() == 2 => {
+                match (p[0].as_f64(), p[1].as_f64()) {
+                    (Some(x), Some(y)) => Ok((x, y)),
+                    _ => Err(err())
+                }
+            }
+            _ => Err(err())
+        })
+        .collect()
+}
+
+fn rss(points: &[(f64, f64)], f: impl Fn(f64) -> f64) -> f64 {
+    points.iter().map(|(x, y)| (y - f(*x)).powi(2)).sum()
+}
+
+fn eval_poly(coeffs: &[f64], x: f64) -> f64 {
+    coeffs.iter().fold(0., |acc, c| acc * x + c)
+}
+
+// least squares polynomial, with coefficients in decreasing order of degree
+pub fn poly_fit(points: &[(f64, f64)], degree: usize) -> Result<Vec<f64>, String> {
+    let n = degree + 1;
+
+    if points.len() < n {
+        return Err(format!("a degree {} fit needs at least {} points", degree, n));
+    }
+
+    let vandermonde: Vec<Vec<f64>> = points.iter()
+        .map(|(x, _)| (0..n).map(|i| x.powi((degree - i) as i32)).collect())
+        .collect();
+
+    let y: Vec<Vec<f64>> = points.iter().map(|(_, y)| vec![*y]).collect();
+
+    least_squares(&vandermonde, &y)
+        .map(|x| x.into_iter().map(|row| row[0]).collect())
+        .map_err(|_| "points do not determine a unique fit".to_string())
+}
+
+fn fit_result(coeffs: Vec<f64>, rss: f64) -> Vec<Item> {
+    vec![List(coeffs.into_iter().map(Num).collect()), Num(rss)]
+}
+
+// takes points and a degree, and returns the coefficients and residual sum
+// of squares
+pub fn poly_fit_item(points: &Item, degree: &Item) -> Result<Vec<Item>, String> {
+    let points = to_points(points)?;
+    let degree = degree.as_rational()
+        .filter(|d| d.is_integer())
+        .and_then(|d| num_traits::ToPrimitive::to_usize(&d.to_integer()))
+        .filter(|d| *d <= 100)
+        .ok_or_else(|| "expected a degree from 0 to 100".to_string())?;
+
+    let coeffs = poly_fit(&points, degree)?;
+    let rss = rss(&points, |x| eval_poly(&coeffs, x));
+
+    Ok(fit_result(coeffs, rss))
+}
+
+// fits a line to transformed points, returning its intercept and slope
+fn transformed_fit(
+    points: &[(f64, f64)],
+    fx: impl Fn(f64) -> f64,
+    fy: impl Fn(f64) -> f64
+) -> Result<(f64, f64), String> {
+    let transformed: Vec<(f64, f64)> = points.iter().map(|(x, y)| (fx(*x), fy(*y))).collect();
+
+    if transformed.iter().any(|(x, y)| !x.is_finite() || !y.is_finite()) {
+        return Err("points are outside the domain of the fit".to_string());
+    }
+
+    let line = poly_fit(&transformed, 1)?;
+
+    Ok((line[1], line[0]))
+}
+
+// y = a * e^(b * x), as [a b]
+pub fn exp_fit_item(points: &Item) -> Result<Vec<Item>, String> {
+    let points = to_points(points)?;
+    let (ln_a, b) = transformed_fit(&points, |x| x, f64::ln)?;
+    let a = ln_a.exp();
+
+    Ok(fit_result(vec![a, b], rss(&points, |x| a * (b * x).exp())))
+}
+
+// y = a + b * ln(x), as [a b]
+pub fn log_fit_item(points: &Item) -> Result<Vec<Item>, String> {
+    let points = to_points(points)?;
+    let (a, b) = transformed_fit(&points, f64::ln, |y| y)?;
+
+    Ok(fit_result(vec![a, b], rss(&points, |x| a + b * x.ln())))
+}
+
+// y = a * x^b, as [a b]
+pub fn power_fit_item(points: &Item) -> Result<Vec<Item>, String> {
+    let points = to_points(points)?;
+    let (ln_a, b) = transformed_fit(&points, f64::ln, f64::ln)?;
+    let a = ln_a.exp();
+
+    Ok(fit_result(vec![a, b], rss(&points, |x| a * x.powf(b))))
+}
@@ -1,15 +1,261 @@
 use crate::modes::*;
 
 use crate::modes::ops::helpers::*;
+use crate::modes::ops::stats;
+use crate::modes::ops::special::*;
+use crate::modes::ops::number_theory::*;
+use crate::modes::ops::matrix;
+use crate::modes::ops::eigen;
+use crate::modes::ops::poly;
+use crate::modes::ops::interp;
+use crate::modes::ops::fft;
+use crate::modes::ops::vector;
+use crate::modes::ops::finance;
 use crate::io::bind_from_str;
+use crate::stack::split_sexagesimal;
+use crate::settings::{self, to_radians, from_radians, angle_scale, Radians, Degrees, Gradians};
+use crate::settings::{DisplayMode, DisplayMode::*, MAX_DIGITS, MAX_DENOMINATOR};
 
 use std::f64::consts;
 use std::f64;
 
-fn add(stack: &mut Stack) {op_2(&|x, y| x + y)(stack)}
-fn sub(stack: &mut Stack) {op_2(&|x, y| x - y)(stack)}
-fn mul(stack: &mut Stack) {op_2(&|x, y| x * y)(stack)}
-fn div(stack: &mut Stack) {op_2(&|x, y| x / y)(stack)}
+use num_rational::BigRational;
+use num_complex::Complex64;
+use num_traits::{Zero, Signed, ToPrimitive};
+
+fn add(stack: &mut Stack) {unit_op_2(Same, &|x, y| x + y, &|x, y| Some(x + y), &|x, y| x + y)(stack)}
+fn sub(stack: &mut Stack) {unit_op_2(Same, &|x, y| x - y, &|x, y| Some(x - y), &|x, y| x - y)(stack)}
+fn mul(stack: &mut Stack) {unit_op_2(Multiply, &|x, y| x * y, &|x, y| Some(x * y), &|x, y| x * y)(stack)}
+fn div(stack: &mut Stack) {unit_op_2(Divide, &|x, y| x / y, &exact_div, &|x, y| x / y)(stack)}
+
+fn exact_div(x: &BigRational, y: &BigRational) -> Option<BigRational> {
+    if y.is_zero() {
+        None
+    } else {
+        Some(x / y)
+    }
+}
+
+fn exact_rem(x: &BigRational, y: &BigRational) -> Option<BigRational> {
+    if y.is_zero() {
+        None
+    } else {
+        Some(x % y)
+    }
+}
+
+// only integer powers are exact; results over a million bits are left to f64
+fn exact_pow(x: &BigRational, y: &BigRational) -> Option<BigRational> {
+    if !y.is_integer() || (x.is_zero() && y.is_negative()) {
+        return None;
+    }
+
+    let e = y.to_integer().to_i32()?;
+    let bits = x.numer().bits().max(x.denom().bits());
+
+    if bits.saturating_mul(e.unsigned_abs() as u64) > 1 << 20 {
+        return None;
+    }
+
+    Some(x.pow(e))
+}
+
+fn exact_recip(x: &BigRational) -> Option<BigRational> {
+    if x.is_zero() {
+        None
+    } else {
+        Some(x.recip())
+    }
+}
+
+fn to_exact(i: Item) -> Item {
+    match i {
+        Num(n) => BigRational::from_float(n).map(Rational).unwrap_or(Num(n)),
+        Integer(w) => Rational(w.to_bigint().into()),
+        _ => i
+    }
+}
+
+// keeps the value of words, and reinterprets their bits when changing sign
+fn to_word(i: Item, bits: Option<u32>, signed: Option<bool>) -> Item {
+    let old = if let Some(w) = i.as_word() {w} else {return Num(f64::NAN)};
+    let bits = bits.unwrap_or(old.bits);
+    let signed = signed.unwrap_or(old.signed);
+
+    if signed == old.signed {
+        Integer(Word::new(bits, signed, old.base).with_int(&old.to_bigint()))
+    } else {
+        Integer(Word::new(bits, signed, old.base).with_raw(old.raw()))
+    }
+}
+
+fn to_base(i: Item, base: u32) -> Item {
+    match i.as_word() {
+        Some(mut w) => {
+            w.base = base;
+            Integer(w)
+        }
+        None => Num(f64::NAN)
+    }
+}
+
+fn shift_left(x: Word, n: i128) -> Word {
+    if n < 0 {
+        shift_right(x, n.checked_neg().unwrap_or(i128::MAX))
+    } else if n >= x.bits as i128 {
+        x.with_raw(0)
+    } else {
+        x.with_raw(x.raw() << n)
+    }
+}
+
+// arithmetic shift for signed words, logical shift for unsigned words
+fn shift_right(x: Word, n: i128) -> Word {
+    if n < 0 {
+        shift_left(x, n.checked_neg().unwrap_or(i128::MAX))
+    } else if x.signed {
+        x.with_int(&(x.to_i128() >> n.min(127)).into())
+    } else if n >= x.bits as i128 {
+        x.with_raw(0)
+    } else {
+        x.with_raw(x.raw() >> n)
+    }
+}
+
+fn rotate_left(x: Word, n: i128) -> Word {
+    let n = n.rem_euclid(x.bits as i128) as u32;
+
+    if n == 0 {
+        x
+    } else {
+        x.with_raw((x.raw() << n) | (x.raw() >> (x.bits - n)))
+    }
+}
+
+fn rotate_right(x: Word, n: i128) -> Word {
+    rotate_left(x, -n.rem_euclid(x.bits as i128))
+}
+
+fn set_bit(x: Word, n: i128, val: bool) -> Word {
+    if n < 0 || n >= x.bits as i128 {
+        x
+    } else if val {
+        x.with_raw(x.raw() | 1 << n)
+    } else {
+        x.with_raw(x.raw() & !(1 << n))
+    }
+}
+
+fn test_bit(x: Item, n: Item) -> Item {
+    match (x.as_word(), as_count(&n)) {
+        (Some(x), Some(n)) => {
+            if n < 0 || n >= x.bits as i128 {
+                Item::exact(0)
+            } else {
+                Item::exact((x.raw() >> n & 1) as i64)
+            }
+        }
+        _ => Num(f64::NAN)
+    }
+}
+
+fn popcount(i: Item) -> Item {
+    match i.as_word() {
+        Some(w) => Item::exact(w.raw().count_ones() as i64),
+        None => Num(f64::NAN)
+    }
+}
+
+fn to_complex(re: Item, im: Item) -> Item {
+    match (re.as_f64(), im.as_f64()) {
+        (Some(re), Some(im)) => complex_item(Complex64::new(re, im)),
+        _ => Num(f64::NAN)
+    }
+}
+
+fn from_polar(r: Item, theta: Item) -> Item {
+    match (r.as_f64(), theta.as_f64()) {
+        (Some(r), Some(theta)) => complex_item(Complex64::from_polar(r, to_radians(theta))),
+        _ => Num(f64::NAN)
+    }
+}
+
+// sets a display mode, with the number of digits taken from the stack
+fn set_display(stack: &mut Stack, mode: fn(usize) -> DisplayMode) {
+    if let Some(x) = stack.pop() {
+        let display = x.as_f64()
+            .filter(|n| *n >= 0. && n.fract() == 0.)
+            .map(|n| mode(n as usize))
+            .filter(|d| d.is_valid());
+
+        match display {
+            Some(d) => settings::update(|s| s.display = d),
+            None => {
+                let err = if let Frac(_) = mode(1) {
+                    format!("expected a largest denominator from 1 to {}", MAX_DENOMINATOR)
+                } else {
+                    format!("expected a number of digits up to {}", MAX_DIGITS)
+                };
+
+                stack.push(x);
+                stack.set_error(err);
+            }
+        }
+    }
+}
+
+// converts decimal hours or degrees to the H.MMSS format, where 1.3045 is one
+// hour, 30 minutes and 45 seconds
+fn to_hms(x: f64) -> f64 {
+    let (sign, units, minutes, seconds) = split_sexagesimal(x, 9);
+    sign * (units + minutes / 100. + seconds / 10000.)
+}
+
+fn from_hms(x: f64) -> f64 {
+    // rounded, so that 1.3 is read as 30 minutes rather than 29.999...
+    let minutes = (x.abs().fract() * 1e11).round() / 1e9;
+    let seconds = minutes.fract() * 100.;
+
+    x.signum() * (x.abs().trunc() + minutes.trunc() / 60. + seconds / 3600.)
+}
+
+// the sign carries through, since trunc rounds towards zero
+fn exact_to_hms(x: &BigRational) -> Option<BigRational> {
+    let minutes = x.fract() * BigRational::from_integer(60.into());
+    let seconds = minutes.fract() * BigRational::from_integer(60.into());
+
+    Some(x.trunc() + minutes.trunc() / BigRational::from_integer(100.into())
+        + seconds / BigRational::from_integer(10000.into()))
+}
+
+fn exact_from_hms(x: &BigRational) -> Option<BigRational> {
+    let minutes = x.fract() * BigRational::from_integer(100.into());
+    let seconds = minutes.fract() * BigRational::from_integer(100.into());
+
+    Some(x.trunc() + minutes.trunc() / BigRational::from_integer(60.into())
+        + seconds / BigRational::from_integer(3600.into()))
+}
+
+fn to_rect(i: Item) -> Item {
+    match i.as_complex() {
+        Some(z) => List(vec![Num(z.re), Num(z.im)]),
+        None => i
+    }
+}
+
+fn to_polar(i: Item) -> Item {
+    match i.as_complex() {
+        Some(z) => List(vec![Num(z.norm()), Num(from_radians(z.arg()))]),
+        None => i
+    }
+}
+
+fn to_approx(i: Item) -> Item {
+    match i.as_f64() {
+        Some(n) => Num(n),
+        None => i
+    }
+}
 
 fn swap(stack: &mut Stack) {
     if stack.len() < 2 {
@@ -145,13 +391,18 @@ fn round_digits(mut x: f64, digits: f64) -> f64 {
     x
 }
 
-fn clean_errors(mut x: f64) -> f64 {
-    if x == 0. {
+fn clean_errors(x: f64) -> f64 {
+    clean_errors_rel(x, x)
+}
+
+// rounds x to 14 significant digits of magnitude
+fn clean_errors_rel(mut x: f64, magnitude: f64) -> f64 {
+    if magnitude == 0. {
         return 0.;
     }
 
     let base: f64 = 10.;
-    let digits = 14 - x.abs().log10() as i32;
+    let digits = 14 - magnitude.abs().log10() as i32;
     let scale = base.powi(digits);
 
     x *= scale;
@@ -161,6 +412,28 @@ fn clean_errors(mut x: f64) -> f64 {
     x
 }
 
+fn clean_errors_complex(z: Complex64) -> Complex64 {
+    let n = z.norm();
+
+    Complex64::new(clean_errors_rel(z.re, n), clean_errors_rel(z.im, n))
+}
+
+// puts back the arguments of an operator which failed on a temporary stack,
+// passing on the error
+fn restore_args(stack: &mut Stack, args: Vec<Item>, error: &str) {
+    for x in args {
+        stack.push(x);
+    }
+    stack.set_error(error.to_string());
+}
+
+fn push_or_restore(stack: &mut Stack, out: Result<Item, String>, args: Vec<Item>) {
+    match out {
+        Ok(out) => stack.push(out),
+        Err(e) => restore_args(stack, args, &e)
+    }
+}
+
 fn synth_div(stack: &mut Stack) {
     let mut l = if let Some(l) = stack.pop_as_list() {l} else {return};
     let x =
@@ -185,6 +458,7 @@ fn synth_div(stack: &mut Stack) {
         }
     }
 
+    let args = vec![x.clone(), List(l.clone())];
     l.reverse();
 
     let mut tmp_stack = Stack::from_vec(l);
@@ -195,6 +469,10 @@ fn synth_div(stack: &mut Stack) {
         tmp_stack.push(x.clone());
         mul(&mut tmp_stack);
         add(&mut tmp_stack);
+
+        if let Some(e) = tmp_stack.get_error() {
+            return restore_args(stack, args, e);
+        }
     }
 
     stack.push(List(res));
@@ -222,13 +500,15 @@ fn poly_div(stack: &mut Stack) {
         return;
     }
 
+    let args = vec![List(poly1.clone()), List(poly2.clone())];
+
     poly1.reverse();
     poly2.reverse();
 
     let rep = poly1.len() - poly2.len();
     let mut tmp = poly2;
 
-    poly2 = vec![Num(0.); rep];
+    poly2 = vec![Item::exact(0); rep];
     poly2.append(&mut tmp);
 
     let mut out = Vec::new();
@@ -248,6 +528,10 @@ fn poly_div(stack: &mut Stack) {
         mul(&mut tmp_stack);
         sub(&mut tmp_stack);
 
+        if let Some(e) = tmp_stack.get_error() {
+            return restore_args(stack, args, e);
+        }
+
         poly1 = tmp_stack.pop_as_list().unwrap();
 
         poly1.pop();
@@ -269,19 +553,297 @@ fn poly_mul(stack: &mut Stack) {
     let len = poly1.len();
     let mut tmp_stack = Stack::new();
 
-    for (i, item) in poly1.into_iter().enumerate() {
+    for (i, item) in poly1.iter().cloned().enumerate() {
         tmp_stack.push(item);
         tmp_stack.push(List(poly2.clone()));
 
         mul(&mut tmp_stack);
 
-        let mut tmp = vec![Num(0.); i];
+        let mut tmp = vec![Item::exact(0); i];
         tmp.append(&mut tmp_stack.pop_as_list().unwrap());
-        tmp.append(&mut vec![Num(0.); len - 1 - i]);
+        tmp.append(&mut vec![Item::exact(0); len - 1 - i]);
 
         tmp_stack.push(List(tmp.clone()));
 
         add(&mut tmp_stack);
+
+        if let Some(e) = tmp_stack.get_error() {
+            return restore_args(stack, vec![List(poly2), List(poly1)], e);
+        }
+    }
+
+    stack.push(tmp_stack.pop().unwrap());
+}
+
+fn is_zero_item(x: &Item, tolerance: f64) -> bool {
+    x.as_complex().is_some_and(|z| z.norm() <= tolerance)
+}
+
+// removes leading coefficients that are within tolerance of zero
+fn trim_poly(mut poly: Vec<Item>, tolerance: f64) -> Vec<Item> {
+    while poly.len() > 1 && is_zero_item(&poly[0], tolerance) {
+        poly.remove(0);
+    }
+
+    poly
+}
+
+// applies a binary operator to the coefficients of two polynomials, padded to
+// the same length
+fn zip_polys(f: fn(&mut Stack), poly1: &[Item], poly2: &[Item]) -> Result<Vec<Item>, String> {
+    let len = poly1.len().max(poly2.len());
+    let pad = |poly: &[Item]| {
+        let mut out = vec![Item::exact(0); len - poly.len()];
+        out.extend_from_slice(poly);
+        out
+    };
+
+    pad(poly1).into_iter().zip(pad(poly2)).map(|(x, y)| apply_2(f, x, y)).collect()
+}
+
+fn poly_zip_op(stack: &mut Stack, f: fn(&mut Stack)) {
+    let poly2 = if let Some(l) = stack.pop_as_list() {l} else {return};
+    let poly1 = if let Some(l) = stack.pop_as_list() {l} else {
+        stack.push(List(poly2));
+        return
+    };
+
+    let out = zip_polys(f, &poly1, &poly2).map(List);
+    push_or_restore(stack, out, vec![List(poly1), List(poly2)]);
+}
+
+fn poly_add(stack: &mut Stack) {
+    poly_zip_op(stack, add);
+}
+
+fn poly_sub(stack: &mut Stack) {
+    poly_zip_op(stack, sub);
+}
+
+fn poly_eval(poly: &[Item], x: &Item) -> Result<Item, String> {
+    poly.iter().try_fold(Item::exact(0), |acc, c| {
+        apply_2(add, apply_2(mul, acc, x.clone())?, c.clone())
+    })
+}
+
+fn poly_derivative(stack: &mut Stack) {
+    let poly = if let Some(l) = stack.pop_as_list() {l} else {return};
+    let n = poly.len();
+
+    if n <= 1 {
+        stack.push(List(vec![Item::exact(0)]));
+        return;
+    }
+
+    let out = poly.iter()
+        .take(n - 1)
+        .enumerate()
+        .map(|(i, c)| apply_2(mul, c.clone(), Item::exact((n - 1 - i) as i64)))
+        .collect::<Result<_, _>>()
+        .map(List);
+
+    push_or_restore(stack, out, vec![List(poly)]);
+}
+
+fn antiderivative(poly: &[Item], constant: Item) -> Result<Vec<Item>, String> {
+    let n = poly.len();
+    let mut out: Vec<Item> = poly.iter()
+        .enumerate()
+        .map(|(i, c)| apply_2(div, c.clone(), Item::exact((n - i) as i64)))
+        .collect::<Result<_, _>>()?;
+
+    out.push(constant);
+    Ok(out)
+}
+
+// takes a polynomial and the constant of integration
+fn poly_antiderivative(stack: &mut Stack) {
+    if stack.len() < 2 {
+        return;
+    }
+
+    let c = stack.pop().unwrap();
+
+    match stack.pop_as_list() {
+        Some(poly) => {
+            let out = antiderivative(&poly, c.clone()).map(List);
+            push_or_restore(stack, out, vec![List(poly), c]);
+        }
+        None => stack.push(c)
+    }
+}
+
+// takes a polynomial and the lower and upper bounds
+fn poly_integral(stack: &mut Stack) {
+    if stack.len() < 3 {
+        return;
+    }
+
+    let b = stack.pop().unwrap();
+    let a = stack.pop().unwrap();
+
+    let poly = if let Some(l) = stack.pop_as_list() {l} else {
+        stack.push(a);
+        stack.push(b);
+        return
+    };
+
+    let out = antiderivative(&poly, Item::exact(0))
+        .and_then(|anti| apply_2(sub, poly_eval(&anti, &b)?, poly_eval(&anti, &a)?));
+
+    push_or_restore(stack, out, vec![List(poly), a, b]);
+}
+
+fn max_coefficient(poly: &[Item]) -> f64 {
+    poly.iter()
+        .filter_map(Item::as_complex)
+        .map(|z| z.norm())
+        .fold(0., f64::max)
+}
+
+// greatest common divisor, scaled to have a leading coefficient of 1
+fn poly_gcd(stack: &mut Stack) {
+    let poly2 = if let Some(l) = stack.pop_as_list() {l} else {return};
+    let poly1 = if let Some(l) = stack.pop_as_list() {l} else {
+        stack.push(List(poly2));
+        return
+    };
+
+    let args = vec![List(poly1.clone()), List(poly2.clone())];
+    let exact = poly1.iter().chain(poly2.iter()).all(|i| matches!(i, Rational(_) | Integer(_)));
+    let tolerance =
+        if exact {
+            0.
+        } else {
+            max_coefficient(&poly1).max(max_coefficient(&poly2)) * 1e-10
+        };
+
+    let mut a = trim_poly(poly1, tolerance);
+    let mut b = trim_poly(poly2, tolerance);
+
+    if a.len() < b.len() {
+        std::mem::swap(&mut a, &mut b);
+    }
+
+    while !b.iter().all(|c| is_zero_item(c, tolerance)) {
+        let mut tmp_stack = Stack::from_vec(vec![List(a), List(b.clone())]);
+        poly_div(&mut tmp_stack);
+
+        if let Some(e) = tmp_stack.get_error() {
+            return restore_args(stack, args, e);
+        }
+
+        let rem = tmp_stack.pop_as_list().unwrap();
+
+        a = b;
+        b = trim_poly(rem, tolerance);
+    }
+
+    // both polynomials were empty
+    let lead = if let Some(c) = a.first() {c.clone()} else {
+        stack.push(List(vec![Item::exact(0)]));
+        return
+    };
+
+    if is_zero_item(&lead, 0.) {
+        stack.push(List(a));
+    } else {
+        let out = a.into_iter()
+            .map(|c| apply_2(div, c, lead.clone()))
+            .collect::<Result<_, _>>()
+            .map(List);
+
+        push_or_restore(stack, out, args);
+    }
+}
+
+// the first polynomial with the second substituted for x
+fn poly_compose(stack: &mut Stack) {
+    let inner = if let Some(l) = stack.pop_as_list() {l} else {return};
+    let outer = if let Some(l) = stack.pop_as_list() {l} else {
+        stack.push(List(inner));
+        return
+    };
+
+    let mut tmp_stack = Stack::new();
+    tmp_stack.push(List(vec![Item::exact(0)]));
+
+    for c in outer.iter().cloned() {
+        tmp_stack.push(List(inner.clone()));
+        poly_mul(&mut tmp_stack);
+        tmp_stack.push(List(vec![c]));
+        poly_add(&mut tmp_stack);
+
+        if let Some(e) = tmp_stack.get_error() {
+            return restore_args(stack, vec![List(outer), List(inner)], e);
+        }
+    }
+
+    let out = tmp_stack.pop_as_list().unwrap();
+
+    stack.push(List(trim_poly(out, 0.)));
+}
+
+// raises a polynomial to a non-negative integer power
+fn poly_pow(stack: &mut Stack) {
+    if stack.len() < 2 {
+        return;
+    }
+
+    let n = stack.pop().unwrap();
+
+    let exp = n.as_rational()
+        .filter(|r| r.is_integer())
+        .and_then(|r| r.to_integer().to_u32())
+        .filter(|e| *e <= 1000);
+
+    let (poly, exp) =
+        match (exp, stack.pop_as_list()) {
+            (Some(e), Some(poly)) => (poly, e),
+            (_, poly) => {
+                if let Some(poly) = poly {
+                    stack.push(List(poly));
+                }
+                stack.push(n);
+                return;
+            }
+        };
+
+    let mut tmp_stack = Stack::new();
+    tmp_stack.push(List(vec![Item::exact(1)]));
+
+    for _ in 0..exp {
+        tmp_stack.push(List(poly.clone()));
+        poly_mul(&mut tmp_stack);
+
+        if let Some(e) = tmp_stack.get_error() {
+            return restore_args(stack, vec![List(poly), n], e);
+        }
+    }
+
+    stack.push(tmp_stack.pop().unwrap());
+}
+
+// multiplies (x - r) together for each root r
+fn poly_from_roots(stack: &mut Stack) {
+    let roots = if let Some(l) = stack.pop_as_list() {l} else {return};
+    let mut tmp_stack = Stack::new();
+
+    tmp_stack.push(List(vec![Item::exact(1)]));
+
+    for r in roots.iter().cloned() {
+        tmp_stack.push(Item::exact(0));
+        tmp_stack.push(r);
+        sub(&mut tmp_stack);
+
+        let neg = tmp_stack.pop().unwrap();
+
+        tmp_stack.push(List(vec![Item::exact(1), neg]));
+        poly_mul(&mut tmp_stack);
+
+        if let Some(e) = tmp_stack.get_error() {
+            return restore_args(stack, vec![List(roots)], e);
+        }
     }
 
     stack.push(tmp_stack.pop().unwrap());
@@ -292,7 +854,7 @@ fn transpose(stack: &mut Stack) {
 
     let new_stack = Stack::from_vec(l);
     stack.push(
-        new_stack.apply_map(&|l| List(l.into_iter().map(Num).collect()))
+        new_stack.apply_map_items(&List)
     );
 }
 
@@ -302,7 +864,7 @@ fn cumsum(stack: &mut Stack) {
     let mut tmp_stack = Stack::new();
 
     for i in l {
-        tmp_stack.push(tmp_stack.last().unwrap_or(&Num(0.)).clone());
+        tmp_stack.push(tmp_stack.last().cloned().unwrap_or_else(|| Item::exact(0)));
         tmp_stack.push(i);
         add(&mut tmp_stack);
     }
@@ -310,22 +872,6 @@ fn cumsum(stack: &mut Stack) {
     stack.push(List(tmp_stack.into_vec()));
 }
 
-fn factorial(n: f64) -> f64 {
-    if n < 0. {
-        return f64::NAN;
-    }
-
-    let mut i = 1.;
-    let mut out: f64 = 1.;
-
-    while i <= n && out.is_finite() {
-        out *= i;
-        i += 1.;
-    }
-
-    out
-}
-
 fn adjacent_difference(stack: &mut Stack) {
     let mut vec = if let Some(l) = stack.pop_as_list() {l} else {return};
     let mut tmp_stack = Stack::new();
@@ -346,62 +892,113 @@ fn adjacent_difference(stack: &mut Stack) {
     stack.push(List(tmp_stack.into_vec()))
 }
 
+fn floor_div_op(stack: &mut Stack) {
+    exact_op_2(&|x, y| float_2(floor_div, x, y), &|x, y| exact_2(floor_div, x, y))(stack)
+}
+
+fn floor_mod_op(stack: &mut Stack) {
+    exact_op_2(&|x, y| float_2(floor_mod, x, y), &|x, y| exact_2(floor_mod, x, y))(stack)
+}
+
+// pushes the floored quotient and remainder
+fn divmod(stack: &mut Stack) {
+    if stack.len() < 2 {
+        return;
+    }
+
+    let y = stack.pop().unwrap();
+    let x = stack.pop().unwrap();
+
+    let mut quot = Stack::from_vec(vec![x.clone(), y.clone()]);
+    let mut rem = Stack::from_vec(vec![x, y]);
+
+    floor_div_op(&mut quot);
+    floor_mod_op(&mut rem);
+
+    stack.push(quot.pop().unwrap());
+    stack.push(rem.pop().unwrap());
+}
+
 pub fn gen_ops() -> Vec<(String, Vec<Vec<Input>>, Op)> {
     vec![
-        ("+"     , vec!["q", "+"        ], op_2(&|x, y| x + y)),
-        ("-"     , vec!["w", "-"        ], op_2(&|x, y| x - y)),
-        ("/"     , vec!["e", "/"        ], op_2(&|x, y| x / y)),
-        ("%"     , vec!["D", "%"        ], op_2(&|x, y| x % y)),
-        ("*"     , vec!["r", "*"        ], op_2(&|x, y| x * y)),
-        ("^"     , vec!["t", "^"        ], op_2(&|x, y| x.powf(y))),
-
-        ("square"   , vec!["oq"            ], op_1(&|x| x * x)),
-        ("sqrt"     , vec!["or"            ], op_1(&|x| x.sqrt())),
-        ("cbrt"     , vec!["ob"            ], op_1(&|x| x.cbrt())),
-        ("nth_rt"   , vec!["on"            ], op_2(&|x, y| x.powf(1. / y))),
-        ("negate"   , vec!["oe"            ], op_1(&|x| -x)),
-        ("invert"   , vec!["oi"            ], op_1(&|x| x.recip())),
-        ("factorial", vec!["of"            ], op_1(&factorial)),
-        ("abs"      , vec!["oab"           ], op_1(&|x| x.abs())),
-
-        ("pow"   , vec!["iwe"              ], op_1(&|x| x.exp())),
-        ("pow2"  , vec!["iws", "iw2", "iww"], op_1(&|x| x.exp2())),
-        ("pow10" , vec!["iwa", "iw1"       ], op_1(&|x| (10f64).powf(x))),
-
-        ("ln"    , vec!["oge"           ], op_1(&|x| x.ln())),
-        ("log10" , vec!["oga", "og1"    ], op_1(&|x| x.log10())),
-        ("log2"  , vec!["ogs", "og2"    ], op_1(&|x| x.log2())),
-        ("log"   , vec!["ogg", "ogl"    ], op_2(&|x, y| x.log(y))),
+        ("+"     , vec!["q", "+"        ], basic(&add)),
+        ("-"     , vec!["w", "-"        ], basic(&sub)),
+        ("/"     , vec!["e", "/"        ], basic(&div)),
+        ("%"     , vec!["D", "%"        ], exact_op_2(&|x, y| x % y, &exact_rem)),
+        ("*"     , vec!["r", "*"        ], basic(&mul)),
+        ("^"     , vec!["t", "^"        ], unit_op_2(Exponent, &|x, y| x.powf(y), &exact_pow, &|x, y| x.powc(y))),
+
+        ("square"   , vec!["oq"            ], unit_op_1(Power(2, 1), &|x| x * x, &|x| Some(x * x), &|x| x * x)),
+        ("sqrt"     , vec!["or"            ], unit_op_1(Power(1, 2), &|x| x.sqrt(), &|_| None, &|x| x.sqrt())),
+        ("cbrt"     , vec!["ob"            ], unit_op_1(Power(1, 3), &|x| x.cbrt(), &|_| None, &|x| x.cbrt())),
+        ("nth_rt"   , vec!["on"            ], unit_op_2(Root, &|x, y| x.powf(1. / y), &|_, _| None, &|x, y| x.powc(y.inv()))),
+        ("negate"   , vec!["oe"            ], unit_op_1(Same, &|x| -x, &|x| Some(-x), &|x| -x)),
+        ("invert"   , vec!["oi"            ], unit_op_1(Power(-1, 1), &|x| x.recip(), &exact_recip, &|x| x.inv())),
+        ("factorial", vec!["of"            ], exact_op_1(&factorial, &exact_factorial)),
+        ("abs"      , vec!["oab"           ], unit_op_1(Same, &|x| x.abs(), &|x| Some(x.abs()), &|x| x.norm().into())),
+
+        ("pow"   , vec!["iwe"              ], complex_op_1(&|x| x.exp(), &|x| x.exp())),
+        ("pow2"  , vec!["iws", "iw2", "iww"], complex_op_1(&|x| x.exp2(), &|x| (x * consts::LN_2).exp())),
+        ("pow10" , vec!["iwa", "iw1"       ], complex_op_1(&|x| (10f64).powf(x), &|x| (x * consts::LN_10).exp())),
+
+        ("ln"    , vec!["oge"           ], complex_op_1(&|x| x.ln(), &|x| x.ln())),
+        ("log10" , vec!["oga", "og1"    ], complex_op_1(&|x| x.log10(), &|x| x.ln() / consts::LN_10)),
+        ("log2"  , vec!["ogs", "og2"    ], complex_op_1(&|x| x.log2(), &|x| x.ln() / consts::LN_2)),
+        ("log"   , vec!["ogg", "ogl"    ], complex_op_2(&|x, y| x.log(y), &|x, y| x.ln() / y.ln())),
+
+        ("->HMS"   , vec!["iht"], exact_op_1(&to_hms, &exact_to_hms)),
+        ("HMS->"   , vec!["ihf"], exact_op_1(&from_hms, &exact_from_hms)),
+        ("HMS+"    , vec!["iha"], exact_op_2(
+            &|x, y| to_hms(from_hms(x) + from_hms(y)),
+            &|x, y| exact_to_hms(&(exact_from_hms(x)? + exact_from_hms(y)?)))),
+        ("HMS-"    , vec!["ihs"], exact_op_2(
+            &|x, y| to_hms(from_hms(x) - from_hms(y)),
+            &|x, y| exact_to_hms(&(exact_from_hms(x)? - exact_from_hms(y)?)))),
 
         ("deg"   , vec!["oad"           ], op_1(&|x| x.to_degrees())),
         ("rad"   , vec!["oar"           ], op_1(&|x| x.to_radians())),
 
-        ("sin"   , vec!["os"            ], op_1(&|x| x.sin())),
-        ("cos"   , vec!["oc"            ], op_1(&|x| x.cos())),
-        ("tan"   , vec!["ot"            ], op_1(&|x| x.tan())),
+        ("sin"   , vec!["os"            ], complex_op_1(&|x| to_radians(x).sin(), &|x| (x * angle_scale()).sin())),
+        ("cos"   , vec!["oc"            ], complex_op_1(&|x| to_radians(x).cos(), &|x| (x * angle_scale()).cos())),
+        ("tan"   , vec!["ot"            ], complex_op_1(&|x| to_radians(x).tan(), &|x| (x * angle_scale()).tan())),
+
+        ("asin"  , vec!["oas"           ], complex_op_1(&|x| from_radians(x.asin()), &|x| x.asin() / angle_scale())),
+        ("acos"  , vec!["oac"           ], complex_op_1(&|x| from_radians(x.acos()), &|x| x.acos() / angle_scale())),
+        ("atan"  , vec!["oat"           ], complex_op_1(&|x| from_radians(x.atan()), &|x| x.atan() / angle_scale())),
 
-        ("asin"  , vec!["oas"           ], op_1(&|x| x.asin())),
-        ("acos"  , vec!["oac"           ], op_1(&|x| x.acos())),
-        ("atan"  , vec!["oat"           ], op_1(&|x| x.atan())),
+        ("angle_mode", vec!["oam"], basic(&|_| settings::update(|s| s.angle = s.angle.next()))),
+        ("rad_mode"  , vec![     ], basic(&|_| settings::update(|s| s.angle = Radians))),
+        ("deg_mode"  , vec![     ], basic(&|_| settings::update(|s| s.angle = Degrees))),
+        ("grad_mode" , vec![     ], basic(&|_| settings::update(|s| s.angle = Gradians))),
 
-        ("sinh"  , vec!["ohs"           ], op_1(&|x| x.sinh())),
-        ("cosh"  , vec!["ohc"           ], op_1(&|x| x.cosh())),
-        ("tanh"  , vec!["oht"           ], op_1(&|x| x.tanh())),
+        ("sinh"  , vec!["ohs"           ], complex_op_1(&|x| x.sinh(), &|x| x.sinh())),
+        ("cosh"  , vec!["ohc"           ], complex_op_1(&|x| x.cosh(), &|x| x.cosh())),
+        ("tanh"  , vec!["oht"           ], complex_op_1(&|x| x.tanh(), &|x| x.tanh())),
 
-        ("asinh" , vec!["ohas"          ], op_1(&|x| x.asinh())),
-        ("acosh" , vec!["ohac"          ], op_1(&|x| x.acosh())),
-        ("atanh" , vec!["ohat"          ], op_1(&|x| x.atanh())),
+        ("asinh" , vec!["ohas"          ], complex_op_1(&|x| x.asinh(), &|x| x.asinh())),
+        ("acosh" , vec!["ohac"          ], complex_op_1(&|x| x.acosh(), &|x| x.acosh())),
+        ("atanh" , vec!["ohat"          ], complex_op_1(&|x| x.atanh(), &|x| x.atanh())),
+
+        ("complex"   , vec!["ixc"], item_op_2(&to_complex)),
+        ("polar"     , vec!["ixp"], item_op_2(&from_polar)),
+        ("re"        , vec!["ixr"], unit_op_1(Same, &|x| x, &|x| Some(x.clone()), &|x| x.re.into())),
+        ("im"        , vec!["ixi"], unit_op_1(Same, &|_| 0., &|_| Some(BigRational::zero()), &|x| x.im.into())),
+        ("conj"      , vec!["ixj"], unit_op_1(Same, &|x| x, &|x| Some(x.clone()), &|x| x.conj())),
+        ("arg"       , vec!["ixa"], complex_op_1(&|x| from_radians(0f64.atan2(x)), &|x| from_radians(x.arg()).into())),
+        ("to_rect"   , vec!["ixv"], item_op_1(&to_rect)),
+        ("to_polar"  , vec!["ixh"], item_op_1(&to_polar)),
 
         ("pi"     , vec!["cp"            ], constant(consts::PI)),
         ("e"      , vec!["ce"            ], constant(consts::E)),
         ("sqrt_2" , vec!["cq"            ], constant(consts::SQRT_2)),
+        ("i"      , vec!["cj"            ], basic(&|st| st.push(Complex(Complex64::i())))),
         ("nan"    , vec!["cn"            ], constant(f64::NAN)),
         ("inf"    , vec!["cip"           ], constant(f64::INFINITY)),
         ("-inf"   , vec!["cin"           ], constant(f64::NEG_INFINITY)),
         ("epsilon", vec!["cs"            ], constant(f64::EPSILON)),
 
-        ("sum"   , vec!["isu", "ou"     ], fold_op(&|x, y| x + y, 0.)),
-        ("msum"  , vec!["ism", "om"     ], fold_op(&|x, y| x * y, 1.)),
+        ("sum"   , vec!["isu", "ou"     ], fold_op(add, 0)),
+        ("msum"  , vec!["ism", "om"     ], fold_op(mul, 1)),
 
         ("clear" , vec!["C", "cc", "isc"], basic(&|st| st.clear())),
         ("swap"  , vec!["isw", "ow"     ], basic(&swap)),
@@ -410,15 +1007,26 @@ pub fn gen_ops() -> Vec<(String, Vec<Vec<Input>>, Op)> {
         ("pop"   , vec!["isp", "op"     ], basic(&|st| {st.pop();})),
         ("rev"   , vec!["isv", "ov"     ], basic(&|st| st.reverse())),
 
-        ("round"       , vec!["ior"      ], op_1(&|x| x.round())),
-        ("floor"       , vec!["iof"      ], op_1(&|x| x.floor())),
-        ("ceil"        , vec!["ioc"      ], op_1(&|x| x.ceil())),
+        ("round"       , vec!["ior"      ], unit_op_1(Same, &|x| x.round(), &|x| Some(x.round()), &|x| Complex64::new(x.re.round(), x.im.round()))),
+        ("floor"       , vec!["iof"      ], unit_op_1(Same, &|x| x.floor(), &|x| Some(x.floor()), &|x| Complex64::new(x.re.floor(), x.im.floor()))),
+        ("ceil"        , vec!["ioc"      ], unit_op_1(Same, &|x| x.ceil(), &|x| Some(x.ceil()), &|x| Complex64::new(x.re.ceil(), x.im.ceil()))),
         ("round_digits", vec!["iodr"     ], op_2(&round_digits)),
-        ("clean_errors", vec!["ioe", "ol"], op_1(&clean_errors)),
+
+        ("fix"     , vec!["iodf"], basic(&|st| set_display(st, Fix))),
+        ("sci"     , vec!["iods"], basic(&|st| set_display(st, Sci))),
+        ("eng"     , vec!["iode"], basic(&|st| set_display(st, Eng))),
+        ("frac"    , vec!["iodq"], basic(&|st| set_display(st, Frac))),
+        ("hms"     , vec!["iodh"], basic(&|_| settings::update(|s| s.display = Hms))),
+        ("dms"     , vec!["iodd"], basic(&|_| settings::update(|s| s.display = Dms))),
+        ("all"     , vec!["ioda"], basic(&|_| settings::update(|s| s.display = All))),
+        ("grouping", vec!["iodg"], basic(&|_| settings::update(|s| s.grouping = !s.grouping))),
+        ("clean_errors", vec!["ioe", "ol"], unit_op_1(Same, &clean_errors, &|x| Some(x.clone()), &clean_errors_complex)),
+        ("exact"       , vec!["ioq"      ], item_op_1(&to_exact)),
+        ("approx"      , vec!["ioa"      ], item_op_1(&to_approx)),
 
         ("new_list" , vec!["iln"], basic(&|st| {st.push(List(Vec::new())); st.down()})),
-        ("sum_list" , vec!["ilu"], list_fold_op(&|x, y| x + y, 0.)),
-        ("msum_list", vec!["ilm"], list_fold_op(&|x, y| x * y, 1.)),
+        ("sum_list" , vec!["ilu"], list_fold_op(add, 0)),
+        ("msum_list", vec!["ilm"], list_fold_op(mul, 1)),
         ("rev_list" , vec!["ilv"], basic(&|st| {st.down(); st.reverse(); st.up()})),
 
         ("range"    , vec!["ila"], basic(&range)),
@@ -429,18 +1037,156 @@ pub fn gen_ops() -> Vec<(String, Vec<Vec<Input>>, Op)> {
         ("adj_diff" , vec!["ild"], basic(&adjacent_difference)),
         ("transpose", vec!["ilt"], basic(&transpose)),
 
+        ("det"      , vec!["imd"], result_op_1(&matrix::det_item)),
+        ("mat_inv"  , vec!["imi"], result_op_1(&matrix::inverse_item)),
+        ("matmul"   , vec!["imm"], result_op_2(&matrix::matmul_item)),
+        ("solve"    , vec!["ims"], result_op_2(&matrix::solve_item)),
+        ("rank"     , vec!["imr"], result_op_1(&matrix::rank_item)),
+        ("trace"    , vec!["imt"], result_op_1(&matrix::trace_item)),
+        ("identity" , vec!["ime"], result_op_1(&matrix::identity_item)),
+        ("zeros"    , vec!["imz"], result_op_2(&matrix::zero_item)),
+        ("lu"       , vec!["iml"], result_op_1(&matrix::lu_item)),
+        ("qr"       , vec!["imq"], result_op_1(&matrix::qr_item)),
+
+        ("eigenvalues" , vec!["imv"], result_op_1(&eigen::eigenvalues_item)),
+        ("eigenvectors", vec!["imw"], result_op_1(&eigen::eigenvectors_item)),
+        ("char_poly"   , vec!["imc"], result_op_1(&eigen::char_poly_item)),
+
         ("synth_sub", vec!["ipp", "ilp"], basic(&synth_sub)),
         ("synth_div", vec!["ips"       ], basic(&synth_div)),
         ("poly_div" , vec!["ipd", "ipe"], basic(&poly_div)),
         ("poly_mul" , vec!["ipm", "ipr"], basic(&poly_mul)),
 
         ("poly_square" , vec!["ipq"], chain(vec![basic(&duplicate), basic(&poly_mul)])),
+        ("poly_add"       , vec!["ipa"], basic(&poly_add)),
+        ("poly_sub"       , vec!["ipb"], basic(&poly_sub)),
+        ("poly_deriv"     , vec!["ipt"], basic(&poly_derivative)),
+        ("poly_antideriv" , vec!["ipi"], basic(&poly_antiderivative)),
+        ("poly_integral"  , vec!["ipn"], basic(&poly_integral)),
+        ("poly_gcd"       , vec!["ipg"], basic(&poly_gcd)),
+        ("poly_compose"   , vec!["ipo"], basic(&poly_compose)),
+        ("poly_pow"       , vec!["ipx"], basic(&poly_pow)),
+        ("poly_roots"     , vec!["ipz"], result_op_1(&poly::poly_roots_item)),
+        ("poly_from_roots", vec!["ipf"], basic(&poly_from_roots)),
+
+        ("mean"      , vec!["istm" ], list_op(&|x| Num(stats::mean(x)))),
+        ("median"    , vec!["istd" ], list_op(&|x| Num(stats::median(x)))),
+        ("mode"      , vec!["isto" ], list_op(&|x| Num(stats::mode(x)))),
+        ("var"       , vec!["istv" ], list_op(&|x| Num(stats::variance(x, 1)))),
+        ("pvar"      , vec!["istpv"], list_op(&|x| Num(stats::variance(x, 0)))),
+        ("std"       , vec!["ists" ], list_op(&|x| Num(stats::variance(x, 1).sqrt()))),
+        ("pstd"      , vec!["istps"], list_op(&|x| Num(stats::variance(x, 0).sqrt()))),
+        ("list_min"  , vec!["isti" ], list_op(&|x| Num(stats::min(x)))),
+        ("list_max"  , vec!["ista" ], list_op(&|x| Num(stats::max(x)))),
+        ("quartiles" , vec!["istq" ], list_op(&stats::quartiles)),
+        ("percentile", vec!["istn" ], list_arg_op(&|x, p| Num(stats::percentile(x, p)))),
+        ("wmean"     , vec!["istw" ], list_op_2(&|x, w| Num(stats::weighted_mean(x, w)))),
+        ("cov"       , vec!["istc" ], list_op_2(&|x, y| Num(stats::covariance(x, y)))),
+        ("corr"      , vec!["istr" ], list_op_2(&|x, y| Num(stats::correlation(x, y)))),
+        ("linreg"    , vec!["istl" ], list_op_2(&stats::linear_regression)),
+
+        ("poly_fit"  , vec!["istfp"], results_op_2(&stats::poly_fit_item)),
+        ("exp_fit"   , vec!["istfe"], results_op_1(&stats::exp_fit_item)),
+        ("log_fit"   , vec!["istfl"], results_op_1(&stats::log_fit_item)),
+        ("power_fit" , vec!["istfw"], results_op_1(&stats::power_fit_item)),
+
+        ("interp_linear"  , vec!["ial"], result_op_2(&|p, x| interp::interp_item(interp::Linear, p, x))),
+        ("interp_lagrange", vec!["iag"], result_op_2(&|p, x| interp::interp_item(interp::Lagrange, p, x))),
+        ("interp_spline"  , vec!["ias"], result_op_2(&|p, x| interp::interp_item(interp::Spline, p, x))),
+        ("interp_func"    , vec!["iaf"], result_op_1(&interp::interp_func_item)),
+
+        ("fft"      , vec!["itf"], result_op_1(&fft::fft_item)),
+        ("ifft"     , vec!["iti"], result_op_1(&fft::ifft_item)),
+        ("magnitude", vec!["itm"], result_op_1(&fft::magnitude_item)),
+        ("phase"    , vec!["itp"], result_op_1(&fft::phase_item)),
+        ("convolve" , vec!["itc"], result_op_2(&fft::convolve_item)),
+        ("correlate", vec!["itr"], result_op_2(&fft::correlate_item)),
+
+        ("npv"      , vec!["icdn"], result_op_2(&finance::npv_item)),
+        ("irr"      , vec!["icdi"], result_op_1(&finance::irr_item)),
+        ("effective", vec!["icme"], result_op_2(&finance::effective_item)),
+        ("nominal"  , vec!["icmn"], result_op_2(&finance::nominal_item)),
 
         ("down"     , vec!["J", "oj"], basic(&|st| st.down())),
         ("up"       , vec!["K", "ok"], basic(&|st| st.up())),
 
-        ("components", vec!["ivc"], vec2_op(&|l, a| (a.cos() * l, a.sin() * l))),
-        ("heading"   , vec!["ivh"], vec2_op(&|x, y| ((x*x + y*y).sqrt(), y.atan2(x)))),
+        ("gamma" , vec!["igg"], op_1(&gamma)),
+        ("lgamma", vec!["igl"], op_1(&lgamma)),
+        ("beta"  , vec!["igb"], op_2(&beta)),
+        ("erf"   , vec!["ige"], op_1(&erf)),
+        ("erfc"  , vec!["igf"], op_1(&erfc)),
+        ("ncr"   , vec!["igc"], exact_op_2(&ncr, &exact_ncr)),
+        ("npr"   , vec!["igp"], exact_op_2(&npr, &exact_npr)),
+
+        ("gcd"       , vec!["ing"], exact_op_2(&|x, y| float_2(gcd, x, y), &|x, y| exact_2(gcd, x, y))),
+        ("lcm"       , vec!["inl"], exact_op_2(&|x, y| float_2(lcm, x, y), &|x, y| exact_2(lcm, x, y))),
+        ("is_prime"  , vec!["inp"], exact_op_1(&|x| float_1(is_prime_int, x), &|x| exact_1(is_prime_int, x))),
+        ("factor"    , vec!["inf"], result_op_1(&factor_item)),
+        ("next_prime", vec!["inu"], exact_op_1(&|x| float_1(next_prime, x), &|x| exact_1(next_prime, x))),
+        ("prev_prime", vec!["ind"], exact_op_1(&|x| float_1(prev_prime, x), &|x| exact_1(prev_prime, x))),
+        ("mod_pow"   , vec!["inm"], item_op_3(&mod_pow_items)),
+        ("mod_inv"   , vec!["ini"], exact_op_2(&|x, y| float_2(mod_inverse, x, y), &|x, y| exact_2(mod_inverse, x, y))),
+        ("totient"   , vec!["int"], result_op_1(&totient_item)),
+        ("floor_div" , vec!["inq"], basic(&floor_div_op)),
+        ("floor_mod" , vec!["ino"], basic(&floor_mod_op)),
+        ("divmod"    , vec!["inr"], basic(&divmod)),
+
+        ("fraction"          , vec!["inx"], result_op_2(&fraction_item)),
+        ("continued_fraction", vec!["inc"], result_op_1(&continued_fraction_item)),
+
+        ("normal_pdf"  , vec!["idnp"], op_3(&normal_pdf)),
+        ("normal_cdf"  , vec!["idnc"], op_3(&normal_cdf)),
+        ("normal_inv"  , vec!["idni"], op_3(&normal_inv)),
+        ("t_pdf"       , vec!["idtp"], op_2(&t_pdf)),
+        ("t_cdf"       , vec!["idtc"], op_2(&t_cdf)),
+        ("t_inv"       , vec!["idti"], op_2(&t_inv)),
+        ("chi2_pdf"    , vec!["idxp"], op_2(&chi2_pdf)),
+        ("chi2_cdf"    , vec!["idxc"], op_2(&chi2_cdf)),
+        ("chi2_inv"    , vec!["idxi"], op_2(&chi2_inv)),
+        ("binomial_pdf", vec!["idbp"], op_3(&binomial_pdf)),
+        ("binomial_cdf", vec!["idbc"], op_3(&binomial_cdf)),
+        ("binomial_inv", vec!["idbi"], op_3(&binomial_inv)),
+        ("poisson_pdf" , vec!["idpp"], op_2(&poisson_pdf)),
+        ("poisson_cdf" , vec!["idpc"], op_2(&poisson_cdf)),
+        ("poisson_inv" , vec!["idpi"], op_2(&poisson_inv)),
+
+        ("and"      , vec!["iba" ], word_op_2(&|x, y| x.with_raw(x.raw() & y.raw()))),
+        ("or"       , vec!["ibo" ], word_op_2(&|x, y| x.with_raw(x.raw() | y.raw()))),
+        ("xor"      , vec!["ibx" ], word_op_2(&|x, y| x.with_raw(x.raw() ^ y.raw()))),
+        ("not"      , vec!["ibn" ], word_op_1(&|x| x.with_raw(!x.raw()))),
+        ("shl"      , vec!["ibl" ], word_count_op(&shift_left)),
+        ("shr"      , vec!["ibr" ], word_count_op(&shift_right)),
+        ("rol"      , vec!["ibtl"], word_count_op(&rotate_left)),
+        ("ror"      , vec!["ibtr"], word_count_op(&rotate_right)),
+        ("popcount" , vec!["ibp" ], item_op_1(&popcount)),
+        ("set_bit"  , vec!["ibks"], word_count_op(&|x, n| set_bit(x, n, true))),
+        ("clear_bit", vec!["ibkc"], word_count_op(&|x, n| set_bit(x, n, false))),
+        ("test_bit" , vec!["ibkt"], item_op_2(&test_bit)),
+
+        ("word8"   , vec!["ib8" ], item_op_1(&|i| to_word(i, Some(8), None))),
+        ("word16"  , vec!["ib16"], item_op_1(&|i| to_word(i, Some(16), None))),
+        ("word32"  , vec!["ib32"], item_op_1(&|i| to_word(i, Some(32), None))),
+        ("word64"  , vec!["ib64"], item_op_1(&|i| to_word(i, Some(64), None))),
+        ("signed"  , vec!["ibzs"], item_op_1(&|i| to_word(i, None, Some(true)))),
+        ("unsigned", vec!["ibzu"], item_op_1(&|i| to_word(i, None, Some(false)))),
+
+        ("hex", vec!["ibdh"], item_op_1(&|i| to_base(i, 16))),
+        ("oct", vec!["ibdo"], item_op_1(&|i| to_base(i, 8))),
+        ("bin", vec!["ibdb"], item_op_1(&|i| to_base(i, 2))),
+        ("dec", vec!["ibdd"], item_op_1(&|i| to_base(i, 10))),
+
+        ("components", vec!["ivc"], vec2_op(&|l, a| (to_radians(a).cos() * l, to_radians(a).sin() * l))),
+        ("heading"   , vec!["ivh"], vec2_op(&|x, y| ((x*x + y*y).sqrt(), from_radians(y.atan2(x))))),
+        ("dot"             , vec!["ivd" ], result_op_2(&vector::dot_item)),
+        ("cross"           , vec!["ivx" ], result_op_2(&vector::cross_item)),
+        ("norm"            , vec!["ivn" ], result_op_1(&vector::norm_item)),
+        ("normalize"       , vec!["ivu" ], result_op_1(&vector::normalize_item)),
+        ("angle"           , vec!["iva" ], result_op_2(&vector::angle_item)),
+        ("project"         , vec!["ivp" ], result_op_2(&vector::project_item)),
+        ("to_spherical"    , vec!["ivs" ], result_op_1(&vector::to_spherical_item)),
+        ("from_spherical"  , vec!["ivfs"], result_op_1(&vector::from_spherical_item)),
+        ("to_cylindrical"  , vec!["ivy" ], result_op_1(&vector::to_cylindrical_item)),
+        ("from_cylindrical", vec!["ivfy"], result_op_1(&vector::from_cylindrical_item)),
     ]
         .into_iter()
         .map(|(name, binds, op)| (
@@ -450,3 +1196,99 @@ pub fn gen_ops() -> Vec<(String, Vec<Vec<Input>>, Op)> {
             ))
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::modes::*;
+    use std::f64::consts;
+
+    fn eval(s: &str) -> String {
+        let mut ui = Ui::build();
+        ui.eval(s.to_string());
+        ui.get_stack().last().map(Item::to_string).unwrap_or_default()
+    }
+
+    #[test]
+    fn shift_counts_are_not_wrapped() {
+        assert_eq!(eval("5i8 128 shl"), "0i8");
+        assert_eq!(eval("5i8 -128 shr"), "0i8");
+        assert_eq!(eval("64i8 255 shl"), "0i8");
+        assert_eq!(eval("-128i8 255 shr"), "-1i8");
+        assert_eq!(eval("4u8 -1 shl"), "2u8");
+        assert_eq!(eval("1u8 -1 ror"), "2u8");
+        assert_eq!(eval("1u8 255 rol"), "128u8");
+        assert_eq!(eval("5i8 256 set_bit"), "5i8");
+        assert_eq!(eval("5i8 256 clear_bit"), "5i8");
+        assert_eq!(eval("5i8 18446744073709551618 test_bit"), "0");
+    }
+
+    #[test]
+    fn sums_keep_complex_numbers_and_units() {
+        assert_eq!(eval("[1 2i 3] sum_list"), "4+2i");
+        assert_eq!(eval("1 2i 3 sum"), "4+2i");
+        assert_eq!(eval("[1 2i] [3 4] 2 msum"), "48i");
+        assert_eq!(eval("[1/3 1/3] sum_list"), "2/3");
+        assert_eq!(eval("[1 _m 2 _m] sum_list"), "3 _m");
+        assert_eq!(eval("[] sum_list"), "0");
+    }
+
+    #[test]
+    fn real_ops_reject_complex_numbers() {
+        for line in ["[1 2i 3] gamma", "2i 3 beta", "0 1 [2 2i] normal_pdf", "[1 _m 2 _s] sum_list"].iter() {
+            let mut ui = Ui::build();
+            ui.eval(line.to_string());
+
+            let mut args = Ui::build();
+            args.eval(line.rsplit_once(' ').unwrap().0.to_string());
+
+            assert!(ui.get_stack().get_error().is_some(), "{}", line);
+            assert_eq!(ui.get_stack().to_disp(80, 10), args.get_stack().to_disp(80, 10), "{}", line);
+        }
+    }
+
+    #[test]
+    fn hms_ops_keep_exact_numbers() {
+        assert_eq!(eval("1.4530 2.3045 HMS+"), "4.1615");
+        assert_eq!(eval("1.3045 2 HMS-"), "-0.2915");
+        assert_eq!(eval("1 3 / ->HMS"), "1/5");
+        assert_eq!(eval("1 7 / ->HMS HMS->"), "1/7");
+        assert_eq!(eval("1.5 ->HMS"), "1.3");
+        assert_eq!(eval("1 _H ->H"), "1 _H");
+    }
+
+    #[test]
+    fn arg_and_phase_follow_the_angle_mode() {
+        assert_eq!(eval("deg_mode 1i arg"), "90");
+        assert_eq!(eval("deg_mode -2 arg"), "180");
+        assert!((eval("grad_mode 1i arg").parse::<f64>().unwrap() - 100.).abs() < 1e-12);
+        assert_eq!(eval("rad_mode -1i arg"), (-consts::FRAC_PI_2).to_string());
+        assert_eq!(eval("deg_mode [0 1 0 -1] phase"), "[ 0 -90 0 90 ]");
+    }
+
+    #[test]
+    fn poly_gcd_of_empty_polynomials() {
+        assert_eq!(eval("[] [] poly_gcd"), "[ 0 ]");
+        assert_eq!(eval("[1 -3 2] [1 -1] poly_gcd"), "[ 1 -1 ]");
+    }
+
+    #[test]
+    fn failed_poly_ops_restore_their_arguments() {
+        let lines = [
+            "[1 _m 2] [3 _s 4] poly_add",
+            "[1 _m 2] [3 _s 4] poly_mul",
+            "[1 _m 2] [1 _s 1] poly_gcd",
+            "[1 _m 2] 0 1 poly_integral",
+        ];
+
+        for line in lines.iter() {
+            let mut ui = Ui::build();
+            ui.eval(line.to_string());
+
+            let mut args = Ui::build();
+            args.eval(line.rsplit_once(' ').unwrap().0.to_string());
+
+            assert!(ui.get_stack().get_error().is_some(), "{}", line);
+            assert_eq!(ui.get_stack().to_disp(80, 10), args.get_stack().to_disp(80, 10), "{}", line);
+        }
+    }
+}
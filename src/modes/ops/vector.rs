@@ -0,0 +1,126 @@
+use crate::modes::*;
+use crate::settings::{to_radians, from_radians};
+
+fn to_vec(x: &Item) -> Result<Vec<f64>, String> {
+    match x {
+        List(l) => l.iter().map(Item::as_f64).collect::<Option<_>>(),
+        _ => None
+    }.ok_or_else(|| "expected a list of numbers".to_string())
+}
+
+fn to_vec3(x: &Item) -> Result<[f64; 3], String> {
+    match to_vec(x)?[..] {
+        [a, b, c] => Ok([a, b, c]),
+        _ => Err("expected a list of 3 numbers".to_string())
+    }
+}
+
+fn to_vec_pair(a: &Item, b: &Item) -> Result<(Vec<f64>, Vec<f64>), String> {
+    let (a, b) = (to_vec(a)?, to_vec(b)?);
+
+    if a.len() != b.len() {
+        return Err(format!("expected vectors of the same length, not {} and {}", a.len(), b.len()));
+    }
+
+    Ok((a, b))
+}
+
+fn from_vec(x: impl IntoIterator<Item = f64>) -> Item {
+    List(x.into_iter().map(Num).collect())
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn norm(a: &[f64]) -> f64 {
+    a.iter().fold(0., |acc, x| acc.hypot(*x))
+}
+
+pub fn dot_item(a: &Item, b: &Item) -> Result<Item, String> {
+    let (a, b) = to_vec_pair(a, b)?;
+    Ok(Num(dot(&a, &b)))
+}
+
+pub fn cross_item(a: &Item, b: &Item) -> Result<Item, String> {
+    let ([a1, a2, a3], [b1, b2, b3]) = (to_vec3(a)?, to_vec3(b)?);
+
+    Ok(from_vec([
+        a2 * b3 - a3 * b2,
+        a3 * b1 - a1 * b3,
+        a1 * b2 - a2 * b1,
+    ]))
+}
+
+pub fn norm_item(a: &Item) -> Result<Item, String> {
+    Ok(Num(norm(&to_vec(a)?)))
+}
+
+pub fn normalize_item(a: &Item) -> Result<Item, String> {
+    let a = to_vec(a)?;
+    let n = norm(&a);
+
+    if n == 0. {
+        return Err("cannot normalize a zero vector".to_string());
+    }
+
+    Ok(from_vec(a.into_iter().map(|x| x / n)))
+}
+
+pub fn angle_item(a: &Item, b: &Item) -> Result<Item, String> {
+    let (a, b) = to_vec_pair(a, b)?;
+    let n = norm(&a) * norm(&b);
+
+    if n == 0. {
+        return Err("the angle to a zero vector is undefined".to_string());
+    }
+
+    // clamped, since rounding can push parallel vectors slightly past 1
+    Ok(Num(from_radians((dot(&a, &b) / n).clamp(-1., 1.).acos())))
+}
+
+// the projection of the first vector onto the second
+pub fn project_item(a: &Item, b: &Item) -> Result<Item, String> {
+    let (a, b) = to_vec_pair(a, b)?;
+    let bb = dot(&b, &b);
+
+    if bb == 0. {
+        return Err("cannot project onto a zero vector".to_string());
+    }
+
+    let scale = dot(&a, &b) / bb;
+    Ok(from_vec(b.into_iter().map(|x| x * scale)))
+}
+
+// spherical coordinates are [r theta phi], with theta measured from the z axis
+// and phi around it from the x axis
+pub fn to_spherical_item(a: &Item) -> Result<Item, String> {
+    let [x, y, z] = to_vec3(a)?;
+    let r = norm(&[x, y, z]);
+    let theta = if r == 0. {0.} else {(z / r).clamp(-1., 1.).acos()};
+
+    Ok(from_vec([r, from_radians(theta), from_radians(y.atan2(x))]))
+}
+
+pub fn from_spherical_item(a: &Item) -> Result<Item, String> {
+    let [r, theta, phi] = to_vec3(a)?;
+    let (theta, phi) = (to_radians(theta), to_radians(phi));
+
+    Ok(from_vec([
+        r * theta.sin() * phi.cos(),
+        r * theta.sin() * phi.sin(),
+        r * theta.cos(),
+    ]))
+}
+
+// cylindrical coordinates are [rho phi z]
+pub fn to_cylindrical_item(a: &Item) -> Result<Item, String> {
+    let [x, y, z] = to_vec3(a)?;
+    Ok(from_vec([x.hypot(y), from_radians(y.atan2(x)), z]))
+}
+
+pub fn from_cylindrical_item(a: &Item) -> Result<Item, String> {
+    let [rho, phi, z] = to_vec3(a)?;
+    let phi = to_radians(phi);
+    Ok(from_vec([rho * phi.cos(), rho * phi.sin(), z]))
+}
@@ -0,0 +1,123 @@
+use crate::modes::*;
+use crate::modes::ops::finance::*;
+
+const REGISTERS: [(&str, &str, Register); 5] = [
+    ("n"  , "n", N),
+    ("i"  , "i", I),
+    ("pv" , "v", Pv),
+    ("pmt", "p", Pmt),
+    ("fv" , "f", Fv),
+];
+
+#[allow(non_camel_case_types)]
+pub struct Tvm_mode {
+    bindings: HashMap<Vec<Input>, String>,
+    tvm: Tvm,
+}
+
+impl Tvm_mode {
+    pub fn new() -> Self {
+        let mut bindings = HashMap::new();
+
+        for (name, key, _) in REGISTERS.iter() {
+            bindings.insert(bind_from_str(&format!("ic{}", key)), format!("sto_{}", name));
+            bindings.insert(bind_from_str(&format!("icr{}", key)), format!("rcl_{}", name));
+            bindings.insert(bind_from_str(&format!("ics{}", key)), format!("solve_{}", name));
+        }
+
+        bindings.insert(bind_from_str("icb"), "tvm_begin".to_string());
+        bindings.insert(bind_from_str("ice"), "tvm_end".to_string());
+        bindings.insert(bind_from_str("icc"), "tvm_clear".to_string());
+        bindings.insert(bind_from_str("icl"), "tvm_list".to_string());
+        bindings.insert(bind_from_str("ica"), "amortize".to_string());
+
+        Tvm_mode {
+            bindings,
+            tvm: Tvm::default(),
+        }
+    }
+}
+
+fn register_from_str(s: &str) -> Register {
+    REGISTERS.iter().find(|(name, _, _)| *name == s).unwrap().2
+}
+
+impl Mode for Tvm_mode {
+    fn get_bindings(&self) -> Vec<Vec<Input>> {
+        self.bindings.keys().cloned().collect()
+    }
+
+    fn get_operator_regex(&self) -> Regex {
+        Regex::new(r"^(sto|rcl|solve)_(n|i|pv|pmt|fv)\b|^tvm_(begin|end|clear|list)\b|^amortize\b").unwrap()
+    }
+
+    fn get_name(&self) -> String {
+        "tvm".to_string()
+    }
+
+    fn eval_operators(&mut self, ui: &mut Ui, op: &str) {
+        let op = op.trim();
+        let stack = ui.get_stack();
+
+        match op {
+            "tvm_begin" => self.tvm.begin = true,
+            "tvm_end" => self.tvm.begin = false,
+            "tvm_clear" => self.tvm = Tvm::default(),
+            "tvm_list" => {
+                let regs = REGISTERS.iter().map(|(_, _, r)| Num(self.tvm.get(*r))).collect();
+                stack.push(List(regs));
+            }
+            "amortize" => match self.tvm.amortize() {
+                Ok(rows) => stack.push(List(
+                    rows.into_iter()
+                        .map(|row| List(row.into_iter().map(Num).collect()))
+                        .collect()
+                )),
+                Err(e) => stack.set_error(e),
+            },
+            _ => {
+                let (word1, word2) = op.split_once('_').unwrap();
+                let reg = register_from_str(word2);
+
+                match word1 {
+                    // pops the number, like storing a variable
+                    "sto" => match stack.last().and_then(Item::as_f64) {
+                        Some(x) => {
+                            stack.pop();
+                            self.tvm.set(reg, x);
+                        }
+                        None => stack.set_error("expected a number".to_string()),
+                    },
+                    "rcl" => stack.push(Num(self.tvm.get(reg))),
+                    _ => match self.tvm.solve(reg) {
+                        Ok(x) => {
+                            self.tvm.set(reg, x);
+                            stack.push(Num(x));
+                        }
+                        Err(e) => stack.set_error(e),
+                    }
+                }
+            }
+        }
+
+        ui.insert_mode(
+            "tvm".to_string(),
+            Box::new(mem::replace(self, Tvm_mode::new()))
+        );
+    }
+
+    fn eval_binding(&mut self, state: &mut State, bind: Vec<Input>)
+        -> Vec<Message>
+    {
+        let op = self.bindings.get(&bind).unwrap().to_string();
+        let len = op.len();
+
+        state.insert("op".to_string(), Str(op.clone()));
+
+        vec![Print(op, len), Return]
+    }
+
+    fn ret(&mut self, state: &mut State) -> String {
+        Data::unwrap_string_or(state.get("op"), String::new())
+    }
+}
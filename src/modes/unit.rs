@@ -0,0 +1,134 @@
+use crate::modes::*;
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Debug)]
+pub struct Unit_mode {}
+
+// converts every number in x to the given units
+fn convert(x: Item, to: &Units) -> Result<Item, String> {
+    match x {
+        List(l) => {
+            l.into_iter()
+                .map(|i| convert(i, to))
+                .collect::<Result<_, _>>()
+                .map(List)
+        }
+        Func(_) => Ok(x),
+        _ => {
+            let (v, from) = x.split_units();
+
+            if from.dim() != to.dim() {
+                return Err(format!("cannot convert {} to {}", from, to));
+            }
+
+            Ok(quantity_item(scale_item(v, from.scale() / to.scale()), to.clone()))
+        }
+    }
+}
+
+impl Mode for Unit_mode {
+    fn get_bindings(&self) -> Vec<Vec<Input>> {
+        vec![
+            vec![Character('_')],
+            vec![Character('>')],
+        ]
+    }
+
+    fn get_operator_regex(&self) -> Regex {
+        // conversions to any units except HMS, which is left for the ->HMS
+        // operator, since the regex crate has no lookahead
+        Regex::new(r"^_\S+|^->(?:[^H\s]|H[^M\s]|HM[^S\s]|HMS\S)\S*|^->HM?\b").unwrap()
+    }
+
+    fn get_name(&self) -> String {
+        "unit".to_string()
+    }
+
+    fn eval_operators(&mut self, ui: &mut Ui, op: &str) {
+        ui.insert_mode("unit".to_string(), Box::new(Unit_mode{}));
+
+        let (convert_to, name) =
+            match op.strip_prefix("->") {
+                Some(name) => (true, name),
+                None => (false, &op[1..])
+            };
+
+        let units =
+            match Units::parse(name) {
+                Some(u) => u,
+                None => {
+                    ui.get_stack().set_error(format!("unknown units: {}", name));
+                    return;
+                }
+            };
+
+        if !convert_to {
+            ui.get_stack().push(Quantity(Box::new(Item::exact(1)), units));
+            ui.eval("*".to_string());
+            return;
+        }
+
+        let stack = ui.get_stack();
+
+        if let Some(x) = stack.pop() {
+            match convert(x.clone(), &units) {
+                Ok(out) => stack.push(out),
+                Err(e) => {
+                    stack.push(x);
+                    stack.set_error(e);
+                }
+            }
+        }
+    }
+
+    fn eval_binding(&mut self, state: &mut State, bind: Vec<Input>)
+        -> Vec<Message>
+    {
+        let prefix = Data::unwrap_string_or(state.get("prefix"), String::new());
+        let mut buffer = Data::unwrap_string_or(state.get("buffer"), String::new());
+        let mut msg = vec![NextKey(true)];
+
+        let prefix =
+            if prefix.is_empty() {
+                match bind.first() {
+                    Some(Character('>')) => "->".to_string(),
+                    _ => "_".to_string()
+                }
+            } else {
+                match bind.first() {
+                    Some(Character(' ')) | Some(Character('\n')) => {
+                        msg = vec![NextKey(false), Return];
+                    }
+                    Some(Character('\u{1b}')) => {
+                        buffer.clear();
+                        msg = vec![NextKey(false), Return];
+                    }
+                    Some(KeyBackspace) => {buffer.pop();}
+                    Some(Character(c)) if !c.is_whitespace() => buffer.push(*c),
+                    _ => {}
+                }
+
+                prefix
+            };
+
+        let print = format!("{}{}", prefix, buffer);
+        let len = print.len();
+
+        msg.insert(0, Print(print, len));
+
+        state.insert("prefix".to_string(), Str(prefix));
+        state.insert("buffer".to_string(), Str(buffer));
+
+        msg
+    }
+
+    fn ret(&mut self, state: &mut State) -> String {
+        let buffer = Data::unwrap_string_or(state.get("buffer"), String::new());
+
+        if buffer.is_empty() {
+            String::new()
+        } else {
+            Data::unwrap_string_or(state.get("prefix"), String::new()) + &buffer
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::cell::Cell;
+use std::f64::consts::PI;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AngleMode {
+    Radians,
+    Degrees,
+    Gradians,
+}
+
+pub use AngleMode::*;
+
+impl AngleMode {
+    // the number of radians in one unit of this angle mode
+    pub fn scale(self) -> f64 {
+        match self {
+            Radians => 1.,
+            Degrees => PI / 180.,
+            Gradians => PI / 200.,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Radians => Degrees,
+            Degrees => Gradians,
+            Gradians => Radians,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Radians => "rad",
+            Degrees => "deg",
+            Gradians => "grad",
+        }
+    }
+
+    fn from_name(s: &str) -> Option<Self> {
+        [Radians, Degrees, Gradians].iter().copied().find(|a| a.name() == s)
+    }
+}
+
+// how numbers are shown on the stack, which never changes their values
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisplayMode {
+    // as many digits as needed to read the number back exactly
+    All,
+    // a fixed number of digits after the decimal point
+    Fix(usize),
+    // scientific notation, with a number of significant digits
+    Sci(usize),
+    // like Sci, with the exponent a multiple of 3 written as an SI prefix
+    Eng(usize),
+    // all digits, followed by the closest fraction with at most this
+    // denominator, as a mixed number
+    Frac(usize),
+    // hours, minutes and seconds, like 1:30:00
+    Hms,
+    // degrees, minutes and seconds, like 1°30'0"
+    Dms,
+}
+
+pub use DisplayMode::*;
+
+// more digits than this are beyond the precision of a float
+pub const MAX_DIGITS: usize = 17;
+pub const MAX_DENOMINATOR: usize = 1000000;
+
+impl DisplayMode {
+    pub fn is_valid(self) -> bool {
+        match self {
+            All | Hms | Dms => true,
+            Fix(n) => n <= MAX_DIGITS,
+            Sci(n) | Eng(n) => (1..=MAX_DIGITS).contains(&n),
+            Frac(n) => (1..=MAX_DENOMINATOR).contains(&n),
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            All => "all".to_string(),
+            Hms => "hms".to_string(),
+            Dms => "dms".to_string(),
+            Fix(n) => format!("fix {}", n),
+            Sci(n) => format!("sci {}", n),
+            Eng(n) => format!("eng {}", n),
+            Frac(n) => format!("frac {}", n),
+        }
+    }
+
+    fn from_name(s: &str) -> Option<Self> {
+        let mut words = s.split_whitespace();
+        let mode = words.next()?;
+        let digits = words.next().and_then(|n| n.parse().ok());
+
+        let out = match (mode, digits) {
+            ("all", None) => All,
+            ("hms", None) => Hms,
+            ("dms", None) => Dms,
+            ("fix", Some(n)) => Fix(n),
+            ("sci", Some(n)) => Sci(n),
+            ("eng", Some(n)) => Eng(n),
+            ("frac", Some(n)) => Frac(n),
+            _ => return None
+        };
+
+        Some(out).filter(|d| d.is_valid())
+    }
+}
+
+// calculator settings which operators and the display depend on, shared by
+// everything running on the same thread
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    pub angle: AngleMode,
+    pub display: DisplayMode,
+    // separate thousands with commas
+    pub grouping: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            angle: Radians,
+            display: All,
+            grouping: false,
+        }
+    }
+}
+
+thread_local! {
+    static SETTINGS: Cell<Settings> = Cell::new(Settings::default());
+}
+
+pub fn get() -> Settings {
+    SETTINGS.with(|s| s.get())
+}
+
+pub fn set(settings: Settings) {
+    SETTINGS.with(|s| s.set(settings));
+}
+
+pub fn update(f: impl FnOnce(&mut Settings)) {
+    let mut settings = get();
+    f(&mut settings);
+    set(settings);
+}
+
+// the number of radians in one unit of the current angle mode
+pub fn angle_scale() -> f64 {
+    get().angle.scale()
+}
+
+// converts an angle in the current angle mode to radians
+pub fn to_radians(x: f64) -> f64 {
+    x * angle_scale()
+}
+
+// converts an angle in radians to the current angle mode
+pub fn from_radians(x: f64) -> f64 {
+    x / angle_scale()
+}
+
+impl Settings {
+    // the indicators shown in the separator line
+    pub fn status(&self) -> String {
+        let mut out = vec![self.angle.name().to_uppercase()];
+
+        if self.display != All {
+            out.push(self.display.name().to_uppercase());
+        }
+
+        out.join(" ")
+    }
+
+    // settings are saved as lines of "key = value"
+    pub fn serialize(&self) -> String {
+        format!(
+            "angle = {}\ndisplay = {}\ngrouping = {}\n",
+            self.angle.name(),
+            self.display.name(),
+            self.grouping
+        )
+    }
+
+    // reads settings saved by serialize, keeping the current value of any
+    // setting which is missing or unreadable
+    pub fn deserialize(&mut self, s: &str) {
+        for line in s.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                let value = value.trim();
+
+                match key.trim() {
+                    "angle" => self.angle = AngleMode::from_name(value).unwrap_or(self.angle),
+                    "display" => self.display = DisplayMode::from_name(value).unwrap_or(self.display),
+                    "grouping" => self.grouping = value.parse().unwrap_or(self.grouping),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_round_trip_through_serialize() {
+        for display in [All, Fix(2), Sci(17), Eng(3), Frac(64), Hms, Dms].iter() {
+            let settings = Settings {angle: Gradians, display: *display, grouping: true};
+            let mut out = Settings::default();
+
+            out.deserialize(&settings.serialize());
+            assert_eq!(out, settings);
+        }
+    }
+
+    #[test]
+    fn unreadable_settings_are_kept() {
+        let mut settings = Settings {angle: Degrees, display: Fix(4), grouping: true};
+
+        settings.deserialize("angle = turns\ndisplay = fix 99\ngrouping = maybe\nunknown = 1");
+        assert_eq!(settings, Settings {angle: Degrees, display: Fix(4), grouping: true});
+    }
+}
@@ -1,7 +1,21 @@
+use num_rational::BigRational;
+use num_complex::Complex64;
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Signed, Zero};
+
+use crate::word::Word;
+use crate::units::Units;
+use crate::settings::{self, DisplayMode, DisplayMode::*};
+use crate::modes::ops::number_theory::closest_fraction;
+
 #[derive(Clone, Debug)]
 pub enum Item {
     List(Vec<Item>),
     Num(f64),
+    Rational(BigRational),
+    Complex(Complex64),
+    Integer(Word),
+    Quantity(Box<Item>, Units),
     Func(String),
 }
 
@@ -9,6 +23,7 @@ pub enum Item {
 pub struct Stack {
     above: Vec<Vec<Item>>,
     curr: Vec<Item>,
+    error: Option<String>,
 }
 
 pub use Item::*;
@@ -19,6 +34,7 @@ impl Stack {
         Stack {
             above: Vec::new(),
             curr: Vec::new(),
+            error: None,
         }
     }
 
@@ -26,6 +42,7 @@ impl Stack {
         Stack {
             above: Vec::new(),
             curr: v.into_iter().map(Num).collect(),
+            error: None,
         }
     }
 
@@ -33,6 +50,7 @@ impl Stack {
         Stack {
             above: Vec::new(),
             curr: v,
+            error: None,
         }
     }
 
@@ -53,10 +71,11 @@ impl Stack {
     }
 
     pub fn pop_as_num(&mut self) -> Option<f64> {
-        match self.pop() {
-            Some(Num(n)) => return Some(n),
-            Some(x) => self.push(x),
-            None => {}
+        if let Some(x) = self.pop() {
+            match x.as_f64() {
+                Some(n) => return Some(n),
+                None => self.push(x)
+            }
         }
         None
     }
@@ -121,7 +140,29 @@ impl Stack {
         self.curr.len()
     }
 
+    // errors are shown to the user until the next line is run
+    pub fn set_error(&mut self, msg: String) {
+        self.error = Some(msg);
+    }
+
+    pub fn get_error(&self) -> Option<&String> {
+        self.error.as_ref()
+    }
+
+    pub fn clear_error(&mut self) {
+        self.error = None;
+    }
+
+    // like apply_map_items, but only for real numbers, giving nan for
+    // anything else
     pub fn apply_map(self, f: &impl Fn(Vec<f64>) -> Item) -> Item {
+        self.apply_map_items(&|v| match v.iter().map(Item::as_f64).collect::<Option<_>>() {
+            Some(v) => f(v),
+            None => Num(f64::NAN)
+        })
+    }
+
+    pub fn apply_map_items(self, f: &impl Fn(Vec<Item>) -> Item) -> Item {
         let mut has_list = false;
 
         for x in self.curr.iter() {
@@ -134,9 +175,9 @@ impl Stack {
         if !has_list {
             let mut input = Vec::new();
 
-            for x in &self.curr {
-                if let Num(n) = x {
-                    input.push(*n);
+            for x in self.curr {
+                if let Num(_) | Rational(_) | Complex(_) | Integer(_) | Quantity(..) = x {
+                    input.push(x);
                 }
             }
 
@@ -147,8 +188,8 @@ impl Stack {
             self.curr.into_iter().map(|i| {
                 let tmp: Box<dyn Iterator<Item = Item>> =
                     match i {
-                        Num(_) | Func(_) => Box::new(std::iter::repeat(i)),
-                        List(l) => Box::new(l.into_iter())
+                        List(l) => Box::new(l.into_iter()),
+                        _ => Box::new(std::iter::repeat(i))
                     };
 
                 tmp
@@ -175,31 +216,11 @@ impl Stack {
             }
 
             if !has_fn {
-                result.push(rec_stack.apply_map(f));
+                result.push(rec_stack.apply_map_items(f));
             }
         }
     }
 
-    pub fn apply_fold_vec(v: &[Item],
-                         f: &impl Fn(f64, f64) -> f64,
-                         mut state: f64) -> f64
-    {
-        for i in v.iter() {
-            match i {
-                Func(_) => {},
-                Num(n) => state = f(state, *n),
-                List(s) => state = Stack::apply_fold_vec(s, f, state)
-            }
-        }
-        state
-    }
-
-    pub fn apply_fold(&self, f: &impl Fn(f64, f64) -> f64, start: f64)
-        -> f64
-    {
-        Stack::apply_fold_vec(&self.curr, f, start)
-    }
-
     pub fn to_disp(&self, width: usize, height: usize) -> String {
         let strs: Vec<String> =
             self.curr.iter().map(|x| x.to_disp(0, width, height)).collect();
@@ -222,7 +243,247 @@ fn show_number(n: f64) -> String {
     }
 }
 
+const SI_PREFIXES: [&str; 17] =
+    ["y", "z", "a", "f", "p", "n", "u", "m", "", "k", "M", "G", "T", "P", "E", "Z", "Y"];
+
+// shows a number on the stack in the current display mode. unlike
+// show_number, this may not be enough to read the number back exactly.
+fn display_number(n: f64) -> String {
+    let settings = settings::get();
+
+    if !n.is_finite() {
+        return show_number(n);
+    }
+
+    let out = match settings.display {
+        All => show_number(n),
+        Fix(d) if n.abs() < 1e12 => format!("{:.*}", d, n),
+        Fix(d) => format!("{:.*e}", d, n),
+        Sci(d) => format!("{:.*e}", d - 1, n),
+        Eng(d) => show_eng(n, d),
+        Frac(d) => show_with_fraction(n, d),
+        Hms | Dms => show_sexagesimal(n, settings.display),
+    };
+
+    if settings.grouping {
+        group_thousands(&out)
+    } else {
+        out
+    }
+}
+
+// engineering notation, with the exponent written as an SI prefix if there is
+// one for it
+fn show_eng(n: f64, digits: usize) -> String {
+    let sci = format!("{:.*e}", digits - 1, n);
+    let (mantissa, exp) = sci.split_once('e').unwrap();
+    let exp: i32 = exp.parse().unwrap();
+
+    let shift = exp.rem_euclid(3);
+    let exp = exp - shift;
+    let mantissa = mantissa.parse::<f64>().unwrap() * 10f64.powi(shift);
+    let mantissa = format!("{:.*}", (digits - 1).saturating_sub(shift as usize), mantissa);
+
+    if (-24..=24).contains(&exp) {
+        format!("{}{}", mantissa, SI_PREFIXES[(exp / 3 + 8) as usize])
+    } else {
+        format!("{}e{}", mantissa, exp)
+    }
+}
+
+// the closest fraction to a number with a denominator of at most max_den,
+// which is either a convergent of its continued fraction, or the largest
+// semiconvergent before the next convergent
+fn show_mixed(num: &BigInt, den: &BigInt) -> String {
+    let whole = num / den;
+    let rem = (num % den).abs();
+
+    if rem.is_zero() {
+        whole.to_string()
+    } else if whole.is_zero() {
+        format!("{}/{}", num, den)
+    } else {
+        format!("{} {}/{}", whole, rem, den)
+    }
+}
+
+// a number followed by the closest fraction to it, marked with ~ if it is not
+// exact, like 0.313 (~5/16)
+fn show_with_fraction(n: f64, max_den: usize) -> String {
+    let x = match BigRational::from_float(n) {
+        Some(x) if n.fract() != 0. && n.abs() < 1e12 => x,
+        _ => return show_number(n)
+    };
+
+    let f = closest_fraction(&x, &max_den.into());
+    let approx = if rational_to_f64(&f) == n {""} else {"~"};
+
+    format!("{} ({}{})", show_number(n), approx, show_mixed(f.numer(), f.denom()))
+}
+
+// splits a number into whole units, minutes and seconds, with the seconds
+// rounded to a number of decimal places so that rounding errors don't show as
+// 59.999... seconds. the sign is returned separately.
+pub fn split_sexagesimal(x: f64, places: i32) -> (f64, f64, f64, f64) {
+    let scale = 10f64.powi(places);
+    let seconds = (x.abs() * 3600. * scale).round() / scale;
+    let units = (seconds / 3600.).floor();
+    let minutes = ((seconds - units * 3600.) / 60.).floor();
+    let seconds = ((seconds - units * 3600. - minutes * 60.) * scale).round() / scale;
+
+    (x.signum(), units, minutes, seconds)
+}
+
+// shown to the nearest millisecond or milliarcsecond
+fn show_sexagesimal(n: f64, mode: DisplayMode) -> String {
+    let (sign, units, minutes, seconds) = split_sexagesimal(n, 3);
+    let sign = if sign < 0. {"-"} else {""};
+
+    if mode == Hms {
+        let pad = if seconds < 10. {"0"} else {""};
+        format!("{}{}:{:02}:{}{}", sign, units, minutes, pad, show_number(seconds))
+    } else {
+        format!("{}{}°{}'{}\"", sign, units, minutes, show_number(seconds))
+    }
+}
+
+// puts commas between each group of three digits before the decimal point
+fn group_thousands(s: &str) -> String {
+    let start = if s.starts_with('-') {1} else {0};
+    let end = s[start..]
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(s.len(), |i| i + start);
+
+    let digits = &s[start..end];
+    let mut out = s[..start].to_string();
+
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+
+    out + &s[end..]
+}
+
+pub fn rational_to_f64(r: &BigRational) -> f64 {
+    r.to_f64().unwrap_or(f64::NAN)
+}
+
+fn show_rational(r: &BigRational) -> String {
+    if r.is_integer() {
+        r.numer().to_string()
+    } else {
+        format!("{}/{}", r.numer(), r.denom())
+    }
+}
+
+// exact numbers are always shown in full, but are grouped like other numbers,
+// and shown as mixed numbers in the fraction display mode
+fn display_rational(r: &BigRational) -> String {
+    let settings = settings::get();
+
+    if let Frac(_) = settings.display {
+        show_mixed(r.numer(), r.denom())
+    } else if let Hms | Dms = settings.display {
+        display_number(rational_to_f64(r))
+    } else if !settings.grouping {
+        show_rational(r)
+    } else if r.is_integer() {
+        group_thousands(&r.numer().to_string())
+    } else {
+        format!("{}/{}", group_thousands(&r.numer().to_string()), group_thousands(&r.denom().to_string()))
+    }
+}
+
+// complex numbers with no imaginary part are stored as plain numbers
+pub fn complex_item(z: Complex64) -> Item {
+    if z.im == 0. {
+        Num(z.re)
+    } else {
+        Complex(z)
+    }
+}
+
+fn show_complex(z: &Complex64, show: impl Fn(f64) -> String) -> String {
+    if z.re == 0. {
+        format!("{}i", show(z.im))
+    } else if z.im.is_sign_negative() {
+        format!("{}-{}i", show(z.re), show(-z.im))
+    } else {
+        format!("{}+{}i", show(z.re), show(z.im))
+    }
+}
+
+// units which cancel out completely are dropped
+pub fn quantity_item(value: Item, units: Units) -> Item {
+    if units.is_empty() {
+        value
+    } else {
+        Quantity(Box::new(value), units)
+    }
+}
+
+// multiplies a value by a conversion factor, keeping it exact if possible
+pub fn scale_item(x: Item, factor: f64) -> Item {
+    if factor == 1. {
+        return x;
+    }
+
+    match x {
+        Complex(z) => complex_item(z * factor),
+        _ => Num(x.as_f64().map_or(f64::NAN, |x| x * factor))
+    }
+}
+
 impl Item {
+    pub fn exact(n: i64) -> Item {
+        Rational(BigRational::from_integer(n.into()))
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Num(n) => Some(*n),
+            Rational(r) => Some(rational_to_f64(r)),
+            Integer(w) => Some(w.to_f64()),
+            _ => None
+        }
+    }
+
+    pub fn as_rational(&self) -> Option<BigRational> {
+        match self {
+            Rational(r) => Some(r.clone()),
+            Integer(w) => Some(BigRational::from_integer(w.to_bigint())),
+            _ => None
+        }
+    }
+
+    // converts real numbers to words, truncating towards zero
+    pub fn as_word(&self) -> Option<Word> {
+        match self {
+            Integer(w) => Some(*w),
+            Rational(r) => Some(Word::default().with_int(&r.trunc().to_integer())),
+            Num(n) if n.is_finite() =>
+                Some(Word::default().with_int(&BigInt::from(*n as i128))),
+            _ => None
+        }
+    }
+
+    pub fn split_units(self) -> (Item, Units) {
+        match self {
+            Quantity(v, u) => (*v, u),
+            _ => (self, Units::new())
+        }
+    }
+
+    pub fn as_complex(&self) -> Option<Complex64> {
+        match self {
+            Complex(z) => Some(*z),
+            _ => self.as_f64().map(Complex64::from)
+        }
+    }
+
     pub fn to_disp(&self, indent: usize, width: usize, height: usize)
         -> String
     {
@@ -252,7 +513,11 @@ impl Item {
                     format!("[{}]", strs.join(" "))
                 }
             },
-            Num(n) => show_number(*n),
+            Num(n) => display_number(*n),
+            Rational(r) => display_rational(r),
+            Complex(z) => show_complex(z, display_number),
+            Integer(w) => w.to_string(),
+            Quantity(v, u) => format!("{}_{}", v.to_disp(indent, width, height), u),
             Func(s) => format!("({})", s),
         }
     }
@@ -303,6 +568,10 @@ impl fmt::Display for Item {
                 write!(f, "[ {} ]", strs.join(" "))
             },
             Num(n) => write!(f, "{}", show_number(*n)),
+            Rational(r) => write!(f, "{}", show_rational(r)),
+            Complex(z) => write!(f, "{}", show_complex(z, show_number)),
+            Integer(w) => write!(f, "{}", w),
+            Quantity(v, u) => write!(f, "{} _{}", v, u),
             Func(s) => write!(f, "( {} )", s),
         }
     }
@@ -0,0 +1,269 @@
+use std::fmt;
+use std::f64::consts::PI;
+
+// exponents of meters, kilograms, seconds, amperes, kelvin, moles, and candela
+pub type Dim = [i32; 7];
+
+const NONE: Dim = [0, 0, 0, 0, 0, 0, 0];
+const LENGTH: Dim = [1, 0, 0, 0, 0, 0, 0];
+const MASS: Dim = [0, 1, 0, 0, 0, 0, 0];
+const TIME: Dim = [0, 0, 1, 0, 0, 0, 0];
+const AREA: Dim = [2, 0, 0, 0, 0, 0, 0];
+const VOLUME: Dim = [3, 0, 0, 0, 0, 0, 0];
+const SPEED: Dim = [1, 0, -1, 0, 0, 0, 0];
+const FREQUENCY: Dim = [0, 0, -1, 0, 0, 0, 0];
+const FORCE: Dim = [1, 1, -2, 0, 0, 0, 0];
+const ENERGY: Dim = [2, 1, -2, 0, 0, 0, 0];
+const POWER: Dim = [2, 1, -3, 0, 0, 0, 0];
+const PRESSURE: Dim = [-1, 1, -2, 0, 0, 0, 0];
+const CHARGE: Dim = [0, 0, 1, 1, 0, 0, 0];
+const VOLTAGE: Dim = [2, 1, -3, -1, 0, 0, 0];
+const RESISTANCE: Dim = [2, 1, -3, -2, 0, 0, 0];
+const CAPACITANCE: Dim = [-2, -1, 4, 2, 0, 0, 0];
+const INDUCTANCE: Dim = [2, 1, -2, -2, 0, 0, 0];
+const MAGNETIC_FIELD: Dim = [0, 1, -2, -1, 0, 0, 0];
+
+// name, size in SI units, dimension, and whether SI prefixes are allowed
+const UNITS: &[(&str, f64, Dim, bool)] = &[
+    ("m"   , 1.                 , LENGTH        , true ),
+    ("g"   , 1e-3               , MASS          , true ),
+    ("s"   , 1.                 , TIME          , true ),
+    ("A"   , 1.                 , [0, 0, 0, 1, 0, 0, 0], true),
+    ("K"   , 1.                 , [0, 0, 0, 0, 1, 0, 0], true),
+    ("mol" , 1.                 , [0, 0, 0, 0, 0, 1, 0], true),
+    ("cd"  , 1.                 , [0, 0, 0, 0, 0, 0, 1], true),
+
+    ("Hz"  , 1.                 , FREQUENCY     , true ),
+    ("N"   , 1.                 , FORCE         , true ),
+    ("J"   , 1.                 , ENERGY        , true ),
+    ("W"   , 1.                 , POWER         , true ),
+    ("Pa"  , 1.                 , PRESSURE      , true ),
+    ("C"   , 1.                 , CHARGE        , true ),
+    ("V"   , 1.                 , VOLTAGE       , true ),
+    ("ohm" , 1.                 , RESISTANCE    , true ),
+    ("F"   , 1.                 , CAPACITANCE   , true ),
+    ("H"   , 1.                 , INDUCTANCE    , true ),
+    ("T"   , 1.                 , MAGNETIC_FIELD, true ),
+    ("L"   , 1e-3               , VOLUME        , true ),
+    ("eV"  , 1.602176634e-19    , ENERGY        , true ),
+    ("cal" , 4.184              , ENERGY        , true ),
+
+    ("rad" , 1.                 , NONE          , false),
+    ("deg" , PI / 180.          , NONE          , false),
+    ("min" , 60.                , TIME          , false),
+    ("h"   , 3600.              , TIME          , false),
+    ("day" , 86400.             , TIME          , false),
+    ("yr"  , 31557600.          , TIME          , false),
+    ("ha"  , 1e4                , AREA          , false),
+
+    ("in"  , 0.0254             , LENGTH        , false),
+    ("ft"  , 0.3048             , LENGTH        , false),
+    ("yd"  , 0.9144             , LENGTH        , false),
+    ("mi"  , 1609.344           , LENGTH        , false),
+    ("nmi" , 1852.              , LENGTH        , false),
+    ("acre", 4046.8564224       , AREA          , false),
+    ("gal" , 3.785411784e-3     , VOLUME        , false),
+    ("oz"  , 0.028349523125     , MASS          , false),
+    ("lb"  , 0.45359237         , MASS          , false),
+    ("ton" , 907.18474          , MASS          , false),
+    ("mph" , 0.44704            , SPEED         , false),
+    ("kph" , 1. / 3.6           , SPEED         , false),
+    ("kn"  , 1852. / 3600.      , SPEED         , false),
+    ("lbf" , 4.4482216152605    , FORCE         , false),
+    ("kgf" , 9.80665            , FORCE         , false),
+    ("bar" , 1e5                , PRESSURE      , false),
+    ("atm" , 101325.            , PRESSURE      , false),
+    ("psi" , 6894.757293168361  , PRESSURE      , false),
+    ("mmHg", 133.322387415      , PRESSURE      , false),
+    ("Btu" , 1055.05585262      , ENERGY        , false),
+    ("hp"  , 745.6998715822702  , POWER         , false),
+];
+
+const PREFIXES: &[(char, f64)] = &[
+    ('p', 1e-12),
+    ('n', 1e-9),
+    ('u', 1e-6),
+    ('m', 1e-3),
+    ('c', 1e-2),
+    ('d', 1e-1),
+    ('k', 1e3),
+    ('M', 1e6),
+    ('G', 1e9),
+    ('T', 1e12),
+];
+
+// returns the size in SI units and dimension of a single unit name
+fn lookup(name: &str) -> Option<(f64, Dim)> {
+    for (n, scale, dim, _) in UNITS {
+        if *n == name {
+            return Some((*scale, *dim));
+        }
+    }
+
+    let mut chars = name.chars();
+    let prefix = chars.next()?;
+    let rest = chars.as_str();
+
+    for (p, pscale) in PREFIXES {
+        if *p == prefix {
+            for (n, scale, dim, prefixable) in UNITS {
+                if *prefixable && *n == rest {
+                    return Some((pscale * scale, *dim));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// A product of named units raised to integer powers, i.e. kg*m/s^2
+#[derive(Clone, Debug, PartialEq)]
+pub struct Units {
+    factors: Vec<(String, i32)>,
+}
+
+impl Units {
+    pub fn new() -> Self {
+        Units {factors: Vec::new()}
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut out = Units::new();
+        let mut parts = s.splitn(2, '/');
+
+        let num = parts.next()?;
+        let den = parts.next();
+
+        for (part, sign) in [(Some(num), 1), (den, -1)].iter() {
+            let part = if let Some(p) = part {p} else {continue};
+
+            for factor in part.split('*') {
+                if factor == "1" && *sign == 1 {
+                    continue;
+                }
+
+                let mut split = factor.splitn(2, '^');
+                let name = split.next()?;
+                let exp = match split.next() {
+                    Some(e) => e.parse::<i32>().ok()?,
+                    None => 1
+                };
+
+                lookup(name)?;
+                out = out.mul(&Units {factors: vec![(name.to_string(), exp * sign)]});
+            }
+        }
+
+        Some(out)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.factors.is_empty()
+    }
+
+    pub fn dim(&self) -> Dim {
+        let mut out = NONE;
+
+        for (name, exp) in &self.factors {
+            let (_, dim) = lookup(name).unwrap();
+
+            for i in 0..out.len() {
+                out[i] += dim[i] * exp;
+            }
+        }
+
+        out
+    }
+
+    // size of these units in SI units
+    pub fn scale(&self) -> f64 {
+        self.factors
+            .iter()
+            .map(|(name, exp)| lookup(name).unwrap().0.powi(*exp))
+            .product()
+    }
+
+    pub fn mul(&self, other: &Units) -> Units {
+        let mut factors = self.factors.clone();
+
+        for (name, exp) in &other.factors {
+            match factors.iter_mut().find(|(n, _)| n == name) {
+                Some((_, e)) => *e += exp,
+                None => factors.push((name.clone(), *exp))
+            }
+        }
+
+        factors.retain(|(_, e)| *e != 0);
+
+        Units {factors}
+    }
+
+    // raises these units to num/den, if all exponents stay integers
+    pub fn pow(&self, num: i32, den: i32) -> Option<Units> {
+        let mut factors = Vec::new();
+
+        for (name, exp) in &self.factors {
+            if den == 0 || (exp * num) % den != 0 {
+                return None;
+            }
+
+            factors.push((name.clone(), exp * num / den));
+        }
+
+        factors.retain(|(_, e)| *e != 0);
+
+        Some(Units {factors})
+    }
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |positive: bool| {
+            self.factors
+                .iter()
+                .filter(|(_, e)| (*e > 0) == positive)
+                .map(|(name, e)| {
+                    if e.abs() == 1 {
+                        name.clone()
+                    } else {
+                        format!("{}^{}", name, e.abs())
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("*")
+        };
+
+        let num = show(true);
+        let den = show(false);
+
+        match (num.is_empty(), den.is_empty()) {
+            (_, true) => write!(f, "{}", num),
+            (true, false) => write!(f, "1/{}", den),
+            (false, false) => write!(f, "{}/{}", num, den),
+        }
+    }
+}
+
+// How an operator treats the units of its arguments
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnitRule {
+    // arguments must be dimensionless, and the result has no units
+    Dimensionless,
+    // arguments are converted to the units of the first
+    Same,
+    Multiply,
+    Divide,
+    // units of the argument are raised to a fixed power
+    Power(i32, i32),
+    // units of the first argument are raised to the second argument
+    Exponent,
+    // units of the first argument are raised to the inverse of the second
+    Root,
+}
@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 use crate::io::*;
 use crate::modes::*;
+use crate::settings;
 
 lazy_static! {
     pub static ref STATE: Arc<Ui> = Arc::new(Ui::build());
@@ -47,6 +48,7 @@ pub fn eval(s: String) {
         ui = Arc::get_mut_unchecked(&mut state);
     }
 
+    ui.get_stack().clear_error();
     ui.eval(s);
 }
 
@@ -72,7 +74,7 @@ pub fn render_html(width: usize, height: usize) -> String {
     lines.append(&mut vec![String::new(); extras]);
     lines.reverse();
 
-    lines.push("=".repeat(width));
+    lines.push(render_separator(width, ui.get_stack().get_error(), &settings::get().status()));
 
     let (cmd, loc) = render_command(&ui.print, ui.cursor, width);
 
@@ -89,3 +91,14 @@ pub fn render_html(width: usize, height: usize) -> String {
 
     lines.join("\n")
 }
+
+// the settings, in a form which can be saved and passed to load_settings
+#[wasm_bindgen]
+pub fn save_settings() -> String {
+    settings::get().serialize()
+}
+
+#[wasm_bindgen]
+pub fn load_settings(s: String) {
+    settings::update(|settings| settings.deserialize(&s));
+}
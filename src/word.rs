@@ -0,0 +1,129 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, ToPrimitive};
+
+use std::fmt;
+
+// A fixed size integer, stored as its raw bits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Word {
+    pub bits: u32,
+    pub signed: bool,
+    pub base: u32,
+    value: u64,
+}
+
+impl Word {
+    pub fn new(bits: u32, signed: bool, base: u32) -> Self {
+        Word {
+            bits,
+            signed,
+            base,
+            value: 0,
+        }
+    }
+
+    pub fn mask(&self) -> u64 {
+        if self.bits >= 64 {
+            u64::MAX
+        } else {
+            (1 << self.bits) - 1
+        }
+    }
+
+    pub fn raw(&self) -> u64 {
+        self.value
+    }
+
+    pub fn with_raw(self, value: u64) -> Self {
+        Word {
+            value: value & self.mask(),
+            ..self
+        }
+    }
+
+    // wraps n around to fit in this word's size
+    pub fn with_int(self, n: &BigInt) -> Self {
+        let modulus = BigInt::one() << self.bits;
+
+        self.with_raw(n.mod_floor(&modulus).to_u64().unwrap())
+    }
+
+    pub fn to_i128(self) -> i128 {
+        if self.signed && (self.value >> (self.bits - 1)) & 1 == 1 {
+            self.value as i128 - (1i128 << self.bits)
+        } else {
+            self.value as i128
+        }
+    }
+
+    pub fn to_bigint(self) -> BigInt {
+        BigInt::from(self.to_i128())
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.to_i128() as f64
+    }
+
+    fn suffix(&self) -> String {
+        format!("{}{}", if self.signed {'i'} else {'u'}, self.bits)
+    }
+}
+
+impl Default for Word {
+    fn default() -> Self {
+        Word::new(64, true, 10)
+    }
+}
+
+// parses literals like "0xFFu8", "0b101", or "-12i16"
+pub fn parse_word(s: &str) -> Option<Word> {
+    let (neg, s) =
+        match s.strip_prefix('-') {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+
+    let (mut word, digits) =
+        match s.find(['u', 'i']) {
+            Some(i) => {
+                let bits = s[i + 1..].parse().ok()?;
+
+                if ![8, 16, 32, 64].contains(&bits) {
+                    return None;
+                }
+
+                (Word::new(bits, &s[i..i + 1] == "i", 10), &s[..i])
+            }
+            None => (Word::default(), s)
+        };
+
+    let (base, digits) =
+        match digits.get(..2) {
+            Some("0x") => (16, &digits[2..]),
+            Some("0o") => (8, &digits[2..]),
+            Some("0b") => (2, &digits[2..]),
+            _ => (10, digits)
+        };
+
+    word.base = base;
+
+    let mut n = BigInt::parse_bytes(digits.as_bytes(), base)?;
+
+    if neg {
+        n = -n;
+    }
+
+    Some(word.with_int(&n))
+}
+
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.base {
+            16 => write!(f, "0x{:X}{}", self.value, self.suffix()),
+            8  => write!(f, "0o{:o}{}", self.value, self.suffix()),
+            2  => write!(f, "0b{:b}{}", self.value, self.suffix()),
+            _  => write!(f, "{}{}", self.to_i128(), self.suffix()),
+        }
+    }
+}
@@ -5,6 +5,13 @@ var height = 40;
 
 async function init() {
     await wasm_bindgen();
+
+    let settings = localStorage.getItem("settings");
+
+    if (settings !== null) {
+        wasm_bindgen.load_settings(settings);
+    }
+
     dynResize();
 }
 
@@ -12,6 +19,7 @@ init();
 
 document.addEventListener("keydown", function (e) {
     wasm_bindgen.eval_key(e.key);
+    localStorage.setItem("settings", wasm_bindgen.save_settings());
     term.innerHTML = wasm_bindgen.render_html(width, height);
 })
 